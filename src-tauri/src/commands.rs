@@ -0,0 +1,142 @@
+use crate::config::Config;
+use crate::devices;
+use crate::folders::{self, Folder};
+use crate::logging::get_log_dir;
+use crate::{
+    get_utility_host, is_utility_url, pages, readiness, run_startup, startup, watchdog,
+    MonitorState, QUIT_EVENT_NAME, WINDOW_NAME,
+};
+use log::{info, warn};
+use serde::Deserialize;
+use std::sync::atomic::Ordering;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
+use tauri_plugin_opener::OpenerExt;
+
+static ZOOM_STEP: f64 = 0.1;
+static ZOOM_MIN: f64 = 0.5;
+static ZOOM_MAX: f64 = 3.0;
+
+#[derive(Debug, Copy, Clone, Deserialize)]
+pub enum ZoomAction {
+    In,
+    Out,
+    Reset,
+}
+
+/// Called by the injected script whenever the page in the Utility's UI changes
+#[tauri::command]
+pub fn report_route(window: WebviewWindow, route: String, config: State<Mutex<Config>>) {
+    // We only track the main window, and only while it's displaying the Utility's UI
+    if window.label() != WINDOW_NAME || !route.starts_with('/') {
+        return;
+    }
+    if let Ok(url) = window.url() {
+        if is_utility_url(window.app_handle(), &url) {
+            readiness::set_page_loaded(window.app_handle());
+            config.lock().unwrap().last_route.replace(route);
+        }
+    }
+}
+
+/// Called by the loading page, to display what we're currently waiting for
+#[tauri::command]
+pub fn startup_progress(handle: AppHandle) -> Option<String> {
+    startup::get_progress(&handle)
+}
+
+/// Called periodically by the injected script, so we can tell if the UI has stopped responding
+#[tauri::command]
+pub fn heartbeat(window: WebviewWindow) {
+    if window.label() != WINDOW_NAME {
+        return;
+    }
+    if let Ok(url) = window.url() {
+        if is_utility_url(window.app_handle(), &url) {
+            watchdog::heartbeat(&window);
+        }
+    }
+}
+
+/// Called by the injected script when a zoom hotkey is pressed
+#[tauri::command]
+pub fn zoom(handle: AppHandle, action: ZoomAction, config: State<Mutex<Config>>) {
+    let mut config = config.lock().unwrap();
+    let zoom = match action {
+        ZoomAction::In => config.zoom + ZOOM_STEP,
+        ZoomAction::Out => config.zoom - ZOOM_STEP,
+        ZoomAction::Reset => 1.0,
+    };
+
+    // Round this off, so we don't slowly drift away from sensible numbers..
+    config.zoom = (zoom.clamp(ZOOM_MIN, ZOOM_MAX) * 10.0).round() / 10.0;
+    for window in devices::get_ui_windows(&handle) {
+        let _ = window.set_zoom(config.zoom);
+    }
+}
+
+/// Called from the error page, attempts to connect to the Utility again
+#[tauri::command]
+pub fn retry(handle: AppHandle, monitor: State<MonitorState>) {
+    pages::show_loading_page(&handle);
+
+    // If we're still connected, it's only the UI which failed to load, so just try that again.
+    if monitor.connected.load(Ordering::Relaxed) {
+        let address = monitor.address.lock().unwrap().clone();
+        if let Some(address) = address {
+            info!("Retrying load of the Utility's UI..");
+            tokio::task::spawn(async move { readiness::load_utility(&handle, &address).await });
+        }
+        return;
+    }
+
+    info!("Retrying connection to the Utility..");
+    tokio::task::spawn(run_startup(handle.clone()));
+}
+
+/// Called from the error page, opens the Utility's UI in the system browser instead
+#[tauri::command]
+pub async fn open_in_browser(handle: AppHandle) -> Result<(), String> {
+    let address = handle
+        .state::<MonitorState>()
+        .address
+        .lock()
+        .unwrap()
+        .clone();
+    let address = match address {
+        Some(address) => address,
+        None => format!("http://{}/", get_utility_host().await?),
+    };
+
+    info!("Opening {} in the system browser", address);
+    handle
+        .opener()
+        .open_url(address, None::<&str>)
+        .map_err(|e| e.to_string())
+}
+
+/// Called from the error page, opens the directory containing our logs
+#[tauri::command]
+pub fn view_logs(handle: AppHandle) -> Result<(), String> {
+    let dir = get_log_dir().ok_or("Unable to locate the log directory")?;
+    handle
+        .opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| {
+            warn!("Unable to open {}: {}", dir.display(), e);
+            e.to_string()
+        })
+}
+
+/// Called from the Utility's UI, opens one of its data folders (eg. 'samples') in the file manager
+#[tauri::command]
+pub async fn open_folder(folder: String) -> Result<(), String> {
+    let folder = Folder::from_name(&folder).ok_or(format!("Unknown folder: {}", folder))?;
+    folders::open(folder).await
+}
+
+/// Called from the error page, shuts down the app
+#[tauri::command]
+pub fn quit(handle: AppHandle) {
+    let _ = handle.emit(QUIT_EVENT_NAME, None::<String>);
+}
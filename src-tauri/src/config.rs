@@ -0,0 +1,235 @@
+use directories::BaseDirs;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, File};
+use std::path::PathBuf;
+
+// This needs to match the identifier in tauri.conf.json, so that our config sits alongside the
+// window state (and anything else tauri stores in the app config directory).
+static APP_IDENTIFIER: &str = "com.frostycoolslug.goxlr-utility-ui";
+static CONFIG_FILE: &str = "settings.json";
+
+/// The Wrapper's own settings, these are separate from the GoXLR Utility's settings file which
+/// is handled by the `--install` and `--remove` code in main.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Start with the Window hidden, the UI will still be loaded in the background
+    pub start_hidden: bool,
+
+    /// What to do when the Window's close button is pressed
+    pub close_behaviour: CloseBehaviour,
+
+    /// Show a tray icon, which can be used to switch between devices
+    pub show_tray: bool,
+
+    /// The last page visited in the Utility's UI, restored when the UI is loaded
+    pub last_route: Option<String>,
+
+    /// The zoom level of the UI
+    pub zoom: f64,
+
+    /// Trust a Utility which is running as a different user
+    pub allow_foreign_daemon: bool,
+
+    /// Open the Utility in the system browser without asking if the window can't be displayed
+    pub browser_fallback: bool,
+
+    /// Workarounds for the Linux webview renderer, these are picked automatically when not set
+    pub renderer_flags: Option<Vec<RendererFlag>>,
+
+    /// How many heartbeats the UI can miss before it's reloaded, 0 disables the watchdog
+    pub heartbeat_missed_beats: u32,
+
+    /// Release the webview after the window has been hidden for this many minutes, 0 disables
+    pub low_memory_minutes: u64,
+
+    /// Which events raise a desktop notification
+    pub notifications: NotificationConfig,
+
+    /// An always on top indicator, displayed while the mic is muted
+    pub mic_overlay: OverlayConfig,
+
+    /// A brief popup showing the level whenever a volume changes
+    pub volume_osd: OsdConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            start_hidden: false,
+            close_behaviour: CloseBehaviour::default(),
+            show_tray: false,
+            last_route: None,
+            zoom: 1.0,
+            allow_foreign_daemon: false,
+            browser_fallback: false,
+            renderer_flags: None,
+            heartbeat_missed_beats: 5,
+            low_memory_minutes: 0,
+            notifications: NotificationConfig::default(),
+            mic_overlay: OverlayConfig::default(),
+            volume_osd: OsdConfig::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub enabled: bool,
+
+    /// The position of the overlay's top left corner on the desktop
+    pub x: f64,
+    pub y: f64,
+
+    pub width: f64,
+    pub height: f64,
+
+    /// Between 0.1 (almost invisible) and 1.0 (fully opaque)
+    pub opacity: f64,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            x: 20.,
+            y: 20.,
+            width: 160.,
+            height: 44.,
+            opacity: 0.85,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OsdConfig {
+    pub enabled: bool,
+
+    /// How long the OSD stays up after the last change, before fading out
+    pub delay_ms: u64,
+
+    /// Only show the OSD while the Utility's UI isn't visible
+    pub only_when_hidden: bool,
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            delay_ms: 1500,
+            only_when_hidden: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// A GoXLR has been attached
+    pub device_connected: bool,
+
+    /// A GoXLR has been removed
+    pub device_disconnected: bool,
+
+    /// The connection to the Utility has been lost (it may be restarting)
+    pub utility_lost: bool,
+
+    /// The connection to the Utility has been restored
+    pub utility_restored: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            device_connected: true,
+            device_disconnected: true,
+            utility_lost: true,
+            utility_restored: true,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseBehaviour {
+    /// Hide the Window, leaving the app running in the background (the original behaviour)
+    #[default]
+    Hide,
+
+    /// Minimize the Window to the taskbar / dock
+    Minimize,
+
+    /// Shut down the App, it'll be started again by the Utility when needed
+    Quit,
+
+    /// Ask the user what to do, optionally remembering their choice
+    Ask,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RendererFlag {
+    /// Disable webkit's DMABUF renderer, needed by a number of Wayland setups
+    DisableDmabuf,
+
+    /// Disable webkit's accelerated compositing
+    DisableCompositing,
+
+    /// Force Mesa to render in software
+    SoftwareRendering,
+
+    /// Run under XWayland rather than Wayland
+    ForceX11,
+}
+
+impl Config {
+    pub fn load() -> Self {
+        let path = match get_config_file() {
+            Some(path) => path,
+            None => return Self::default(),
+        };
+
+        if !path.exists() {
+            return Self::default();
+        }
+
+        match File::open(&path) {
+            Ok(reader) => serde_json::from_reader(reader).unwrap_or_else(|e| {
+                warn!("Unable to parse {}: {}, using defaults", path.display(), e);
+                Self::default()
+            }),
+            Err(e) => {
+                warn!("Unable to open {}: {}, using defaults", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let path = get_config_file().ok_or("Unable to locate the config directory")?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| format!("Unable to create config path: {}", e))?;
+        }
+
+        let writer = File::create(&path)
+            .map_err(|e| format!("Unable to open {} for writing: {}", path.display(), e))?;
+        serde_json::to_writer_pretty(writer, self)
+            .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+    }
+}
+
+fn get_config_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.config_dir().join(APP_IDENTIFIER))
+}
+
+/// The directory used for things which aren't config, such as logs
+pub fn get_data_dir() -> Option<PathBuf> {
+    BaseDirs::new().map(|dirs| dirs.data_local_dir().join(APP_IDENTIFIER))
+}
+
+fn get_config_file() -> Option<PathBuf> {
+    get_config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
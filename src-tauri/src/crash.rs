@@ -0,0 +1,110 @@
+use crate::config::get_data_dir;
+use crate::logging::get_log_file;
+use crate::show_option;
+use log::error;
+use std::backtrace::Backtrace;
+use std::fs::{create_dir_all, read_to_string, write};
+use std::panic::PanicHookInfo;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static LOG_LINES: usize = 50;
+
+/// Only report the first panic, anything after that is likely a consequence of it.
+static REPORTED: AtomicBool = AtomicBool::new(false);
+
+/// Replaces the default panic handler, so rather than silently disappearing (we generally have
+/// no console attached), a crash report is written and the user is told about it.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        let report = build_report(info);
+        error!("{}", report);
+
+        if REPORTED.swap(true, Ordering::Relaxed) {
+            return;
+        }
+
+        let path = match write_report(&report) {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Unable to write crash report: {}", e);
+                crate::show_error("GoXLR Utility UI has Crashed".into(), report);
+                return;
+            }
+        };
+
+        let title = String::from("GoXLR Utility UI has Crashed");
+        let message = format!(
+            "{}\n\nA crash report has been written to:\n{}\n\nOpen the containing folder?",
+            get_message(info),
+            path.display()
+        );
+
+        // On macOS dialogs have to come from the main thread, if we're not on it, we can't wait
+        // for an answer, so just show the error.
+        #[cfg(target_os = "macos")]
+        if crate::macos::MainThreadMarker::new().is_none() {
+            crate::show_error(title, message);
+            return;
+        }
+
+        if show_option(title, message).is_ok() {
+            let _ = tauri_plugin_opener::reveal_item_in_dir(&path);
+        }
+    }));
+}
+
+fn get_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("Unknown Error")
+    }
+}
+
+fn build_report(info: &PanicHookInfo) -> String {
+    let thread = thread::current();
+    let location = info
+        .location()
+        .map(|location| location.to_string())
+        .unwrap_or_default();
+
+    let mut report = format!(
+        "GoXLR Utility UI {} ({} {})\nThread '{}' panicked at {}:\n{}\n\nBacktrace:\n{}",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("<unnamed>"),
+        location,
+        get_message(info),
+        Backtrace::force_capture()
+    );
+
+    if let Some(log) = get_log_file().and_then(|path| read_to_string(path).ok()) {
+        let lines: Vec<&str> = log.lines().collect();
+        let start = lines.len().saturating_sub(LOG_LINES);
+        report.push_str("\nRecent Log:\n");
+        report.push_str(&lines[start..].join("\n"));
+    }
+    report
+}
+
+fn write_report(report: &str) -> Result<PathBuf, String> {
+    let dir = get_data_dir()
+        .ok_or("Unable to locate the data directory")?
+        .join("crashes");
+    create_dir_all(&dir).map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_secs())
+        .unwrap_or_default();
+    let path = dir.join(format!("crash-{}.txt", time));
+    write(&path, report).map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
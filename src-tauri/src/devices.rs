@@ -0,0 +1,219 @@
+use crate::config::Config;
+use crate::state::{Change, StateStore};
+use crate::{
+    fallback, is_utility_url, navigation, title, tray, MonitorState, READY_EVENT_NAME,
+    UTILITY_SCRIPT, WINDOW_NAME,
+};
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Url, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+use tokio::sync::broadcast::error::RecvError;
+
+pub static DEVICE_WINDOW_PREFIX: &str = "device-";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Device {
+    pub serial: String,
+    pub device_type: String,
+}
+
+impl Device {
+    pub fn model(&self) -> &'static str {
+        match self.device_type.as_str() {
+            "Mini" => "GoXLR Mini",
+            _ => "GoXLR",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("{} ({})", self.model(), self.serial)
+    }
+}
+
+/// The devices currently attached to the Utility, sorted by serial. The first device is shown
+/// in the main window, and every other device is given a window of its own.
+#[derive(Default)]
+pub struct DeviceState {
+    pub devices: Mutex<Vec<Device>>,
+}
+
+/// Returns the devices present in a `DaemonStatus`
+pub fn get_devices(status: &Value) -> Vec<Device> {
+    let mut devices = vec![];
+    if let Some(mixers) = status.get("mixers").and_then(Value::as_object) {
+        for (serial, mixer) in mixers {
+            let device_type = mixer
+                .get("hardware")
+                .and_then(|hardware| hardware.get("device_type"))
+                .and_then(Value::as_str)
+                .unwrap_or("Unknown");
+
+            devices.push(Device {
+                serial: serial.clone(),
+                device_type: device_type.to_string(),
+            });
+        }
+    }
+    devices.sort_by(|a, b| a.serial.cmp(&b.serial));
+    devices
+}
+
+/// Returns the label of the window responsible for a specific device
+pub fn get_window_label(devices: &[Device], serial: &str) -> String {
+    if devices
+        .first()
+        .is_some_and(|device| device.serial == serial)
+    {
+        WINDOW_NAME.to_string()
+    } else {
+        format!("{}{}", DEVICE_WINDOW_PREFIX, serial)
+    }
+}
+
+pub fn is_device_window(label: &str) -> bool {
+    label.starts_with(DEVICE_WINDOW_PREFIX)
+}
+
+/// Whether a window displays the Utility's UI, rather than being an overlay or OSD
+pub fn is_ui_window(label: &str) -> bool {
+    label == WINDOW_NAME || is_device_window(label)
+}
+
+/// Returns the windows which display the Utility's UI, rather than any overlays
+pub fn get_ui_windows(handle: &AppHandle) -> Vec<WebviewWindow> {
+    handle
+        .webview_windows()
+        .into_iter()
+        .filter(|(label, _)| is_ui_window(label))
+        .map(|(_, window)| window)
+        .collect()
+}
+
+/// Follows the Utility's state, keeping the titles up to date, and opening / closing windows as
+/// devices are attached and removed.
+pub async fn device_monitor(handle: AppHandle) {
+    let mut changes = handle.state::<StateStore>().subscribe();
+
+    loop {
+        match changes.recv().await {
+            // Volumes change constantly, and don't affect anything here.
+            Ok(Change::VolumeChanged { .. }) | Ok(Change::Disconnected) => continue,
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+
+        if let Some(status) = handle.state::<StateStore>().get_status() {
+            let devices = get_devices(&status);
+            title::update_titles(&handle, &status, &devices);
+            update_devices(&handle, &status, devices);
+        }
+    }
+}
+
+fn update_devices(handle: &AppHandle, status: &Value, devices: Vec<Device>) {
+    let state = handle.state::<DeviceState>();
+    {
+        let mut known = state.devices.lock().unwrap();
+        if *known == devices {
+            return;
+        }
+        known.clone_from(&devices);
+    }
+
+    let names: Vec<String> = devices.iter().map(Device::name).collect();
+    info!("Attached devices changed: {:?}", names);
+
+    // Close any windows for devices which are no longer present (or are now in the main window)
+    let labels: Vec<String> = devices
+        .iter()
+        .skip(1)
+        .map(|device| get_window_label(&devices, &device.serial))
+        .collect();
+    for (label, window) in handle.webview_windows() {
+        if is_device_window(&label) && !labels.contains(&label) {
+            let _ = window.destroy();
+        }
+    }
+
+    // Device windows follow the visibility of the main window, so we don't pop up while hidden.
+    // If the main window has been released, these will be created when it's restored, and
+    // without a working webview the devices can be picked in the browser instead.
+    let main = handle.get_webview_window(WINDOW_NAME);
+    let visible = main
+        .as_ref()
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    let create = main.is_some() && !fallback::is_active(handle);
+
+    let http_address = handle
+        .state::<MonitorState>()
+        .address
+        .lock()
+        .unwrap()
+        .clone();
+
+    // The main window shows the first device, if that's changed it needs to be reloaded.
+    if let (Some(main), Some(first), Some(address)) = (&main, devices.first(), &http_address) {
+        let loaded = get_loaded_serial(handle, main);
+        if loaded.is_some_and(|serial| serial.as_ref() != Some(&first.serial)) {
+            info!("Reloading the main window for {}", first.name());
+            let _ = handle.emit(READY_EVENT_NAME, address);
+        }
+    }
+
+    for (device, label) in devices.iter().skip(1).zip(labels) {
+        if !create || handle.get_webview_window(&label).is_some() {
+            continue;
+        }
+        let Some(http_address) = &http_address else {
+            continue;
+        };
+        let title = title::get_device_title(status, device);
+        if let Err(e) = create_device_window(handle, http_address, &title, device, &label, visible)
+        {
+            warn!("Unable to create window for {}: {}", device.serial, e);
+        }
+    }
+
+    if let Err(e) = tray::update_tray(handle, &devices) {
+        warn!("Unable to update tray: {}", e);
+    }
+}
+
+/// Returns the serial a window has loaded the Utility's UI for, or None if it's not showing the UI
+fn get_loaded_serial(handle: &AppHandle, window: &WebviewWindow) -> Option<Option<String>> {
+    let url = window.url().ok()?;
+    if !is_utility_url(handle, &url) {
+        return None;
+    }
+    let serial = url.query_pairs().find(|(key, _)| key == "serial");
+    Some(serial.map(|(_, serial)| serial.into_owned()))
+}
+
+fn create_device_window(
+    handle: &AppHandle,
+    http_address: &str,
+    title: &str,
+    device: &Device,
+    label: &str,
+    visible: bool,
+) -> Result<(), String> {
+    // Let the UI know which device this window is for..
+    let mut url = Url::parse(http_address).map_err(|e| e.to_string())?;
+    url.query_pairs_mut().append_pair("serial", &device.serial);
+
+    let window = WebviewWindowBuilder::new(handle, label, WebviewUrl::External(url))
+        .title(title)
+        .inner_size(1300., 910.)
+        .visible(visible)
+        .initialization_script(UTILITY_SCRIPT)
+        .on_navigation(navigation::navigation_guard(handle.clone()))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    // Match the zoom of the main window
+    let zoom = handle.state::<Mutex<Config>>().lock().unwrap().zoom;
+    let _ = window.set_zoom(zoom);
+    Ok(())
+}
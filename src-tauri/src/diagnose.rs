@@ -0,0 +1,290 @@
+use crate::config::{get_data_dir, Config};
+use crate::ipc;
+use crate::logging::get_log_dir;
+use crate::readiness;
+use crate::{get_current_path, get_goxlr_host};
+use directories::BaseDirs;
+use serde_json::{json, Map, Value};
+use std::env;
+use std::fs::{create_dir_all, File};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static REPORT_PREFIX: &str = "goxlr-utility-ui-diagnostics";
+
+/// Environment variables which affect how webkit renders, or how we find our dialogs.
+static ENVIRONMENT: &[&str] = &[
+    "XDG_SESSION_TYPE",
+    "XDG_CURRENT_DESKTOP",
+    "WAYLAND_DISPLAY",
+    "DISPLAY",
+    "APPIMAGE",
+    "WEBKIT_DISABLE_DMABUF_RENDERER",
+    "WEBKIT_DISABLE_COMPOSITING_MODE",
+    "WEBKIT_FORCE_SANDBOX",
+    "GDK_BACKEND",
+    "LIBGL_ALWAYS_SOFTWARE",
+    "GOXLR_UI_RENDERER_FLAGS",
+    "RUST_LOG",
+];
+
+/// Collects everything we're likely to be asked for in a support request, and writes it to a
+/// single JSON file which can be attached to an issue.
+pub async fn run(output: Option<PathBuf>) -> Result<PathBuf, String> {
+    let mut report = Map::new();
+    report.insert("wrapper".into(), get_wrapper_info());
+    report.insert("environment".into(), get_environment());
+
+    // Now see what the Utility has to say for itself..
+    let mut utility = Map::new();
+    utility.insert("socket".into(), json!(ipc::get_socket_path()));
+
+    match ipc::get_status().await.map_err(|e| e.to_string()) {
+        Ok(status) => {
+            utility.insert("connected".into(), json!(true));
+            utility.insert("activation".into(), get_activation(&status));
+
+            match get_goxlr_host(&status) {
+                Ok(host) => {
+                    utility.insert("host".into(), json!(host));
+                    utility.insert("http".into(), check_http(&host).await);
+                    utility.insert("websocket".into(), check_websocket(&host));
+                }
+                Err(e) => {
+                    utility.insert("host_error".into(), json!(e));
+                }
+            }
+            utility.insert("status".into(), redact(&status));
+        }
+        Err(e) => {
+            utility.insert("connected".into(), json!(false));
+            utility.insert("error".into(), json!(e));
+        }
+    }
+    report.insert("utility".into(), Value::Object(utility));
+
+    let path = match output {
+        Some(path) => path,
+        None => {
+            let dir = get_data_dir().ok_or("Unable to locate the data directory")?;
+            create_dir_all(&dir)
+                .map_err(|e| format!("Unable to create {}: {}", dir.display(), e))?;
+
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|time| time.as_secs())
+                .unwrap_or_default();
+            dir.join(format!("{}-{}.json", REPORT_PREFIX, time))
+        }
+    };
+
+    let writer = File::create(&path)
+        .map_err(|e| format!("Unable to open {} for writing: {}", path.display(), e))?;
+    serde_json::to_writer_pretty(writer, &Value::Object(report))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))?;
+    Ok(path)
+}
+
+fn get_wrapper_info() -> Value {
+    json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": env::consts::OS,
+        "arch": env::consts::ARCH,
+        "executable": redact_path(&get_current_path().to_string_lossy()),
+        "config": redact_config(Config::load()),
+        "log_dir": get_log_dir().map(|dir| redact_path(&dir.to_string_lossy())),
+    })
+}
+
+fn get_environment() -> Value {
+    let mut environment = Map::new();
+    for key in ENVIRONMENT {
+        let value = env::var(key).ok().map(|value| redact_path(&value));
+        environment.insert(key.to_string(), json!(value));
+    }
+
+    // On Linux, errors are displayed via kdialog or zenity, so make sure they're present.
+    #[cfg(target_os = "linux")]
+    {
+        let mut dialogs = Map::new();
+        for dialog in ["kdialog", "zenity"] {
+            dialogs.insert(dialog.into(), json!(find_in_path(dialog).is_some()));
+        }
+        environment.insert("dialogs".into(), Value::Object(dialogs));
+
+        // If the renderer has previously crashed, we'll be running with the compatibility flags
+        let marker = crate::renderer::get_crash_marker().filter(|path| path.exists());
+        environment.insert("renderer_crashed".into(), json!(marker.is_some()));
+    }
+    Value::Object(environment)
+}
+
+#[cfg(target_os = "linux")]
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|path| path.join(binary))
+            .find(|path| path.is_file())
+    })
+}
+
+fn get_activation(status: &Value) -> Value {
+    let activation = status
+        .get("Status")
+        .and_then(|status| status.get("config"))
+        .and_then(|config| config.get("activation"));
+
+    match activation {
+        Some(activation) => {
+            let path = activation.get("active_path").and_then(Value::as_str);
+            let current = get_current_path();
+            json!({
+                "active_path": path.map(redact_path),
+                "is_this_app": path.is_some_and(|path| path.contains(&*current.to_string_lossy())),
+            })
+        }
+        None => json!("Unsupported (pre-1.0.6 Utility)"),
+    }
+}
+
+async fn check_http(host: &str) -> Value {
+    let url = format!("http://{}/", host);
+    match readiness::get_status_line(host, "/").await {
+        Ok(status) => json!({ "url": url, "reachable": true, "response": status }),
+        Err(e) => json!({ "url": url, "reachable": false, "error": e.to_string() }),
+    }
+}
+
+fn check_websocket(host: &str) -> Value {
+    let url = format!("ws://{}/api/websocket", host);
+    match tungstenite::connect(url.as_str()) {
+        Ok((mut socket, _)) => {
+            let _ = socket.close(None);
+            json!({ "url": url, "reachable": true })
+        }
+        Err(e) => json!({ "url": url, "reachable": false, "error": e.to_string() }),
+    }
+}
+
+/// The saved route carries the serial of the device it was for in its query, so that's removed
+fn redact_config(mut config: Config) -> Value {
+    config.last_route = config.last_route.map(|route| strip_query(&route));
+    json!(config)
+}
+
+fn strip_query(route: &str) -> String {
+    let Some((path, query)) = route.split_once('?') else {
+        return route.to_string();
+    };
+    match query.split_once('#') {
+        Some((_, fragment)) => format!("{}#{}", path, fragment),
+        None => path.to_string(),
+    }
+}
+
+/// Removes anything from the status which could identify the user, device serials are replaced
+/// with placeholders, and the user's home directory is removed from any paths.
+fn redact(status: &Value) -> Value {
+    let mut serials = vec![];
+    if let Some(mixers) = status
+        .get("Status")
+        .and_then(|status| status.get("mixers"))
+        .and_then(Value::as_object)
+    {
+        serials.extend(mixers.keys().cloned());
+    }
+    redact_value(status, &serials)
+}
+
+fn redact_value(value: &Value, serials: &[String]) -> Value {
+    match value {
+        Value::String(string) => Value::String(redact_string(string, serials)),
+        Value::Array(array) => Value::Array(
+            array
+                .iter()
+                .map(|value| redact_value(value, serials))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (redact_string(key, serials), redact_value(value, serials)))
+                .collect(),
+        ),
+        _ => value.clone(),
+    }
+}
+
+fn redact_string(string: &str, serials: &[String]) -> String {
+    let mut string = redact_path(string);
+    for (index, serial) in serials.iter().enumerate() {
+        string = string.replace(serial, &format!("<SERIAL-{}>", index + 1));
+    }
+    string
+}
+
+fn redact_path(path: &str) -> String {
+    if let Some(dirs) = BaseDirs::new() {
+        let home = dirs.home_dir().to_string_lossy().to_string();
+        if !home.is_empty() {
+            return path.replace(&home, "~");
+        }
+    }
+    path.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_serials() {
+        let status = json!({"Status": {
+            "mixers": {"S201200586CQK": {"hardware": {"serial_number": "S201200586CQK"}}},
+            "files": ["Profile for S201200586CQK"]
+        }});
+
+        let redacted = redact(&status);
+        let mixers = redacted["Status"]["mixers"].as_object().unwrap();
+        assert!(mixers.contains_key("<SERIAL-1>"));
+        assert_eq!(
+            mixers["<SERIAL-1>"]["hardware"]["serial_number"],
+            "<SERIAL-1>"
+        );
+        assert_eq!(redacted["Status"]["files"][0], "Profile for <SERIAL-1>");
+        assert!(!redacted.to_string().contains("S201200586CQK"));
+    }
+
+    #[test]
+    fn redacts_home_directory() {
+        let Some(dirs) = BaseDirs::new() else {
+            return;
+        };
+        let home = dirs.home_dir().join("GoXLR").join("Profiles");
+        let status = json!({"Status": {"paths": {"profile_directory": home}}});
+
+        let redacted = redact(&status);
+        let path = redacted["Status"]["paths"]["profile_directory"]
+            .as_str()
+            .unwrap();
+        assert!(path.starts_with('~'), "{}", path);
+    }
+
+    #[test]
+    fn strips_serial_from_route() {
+        assert_eq!(strip_query("/?serial=S201200586CQK"), "/");
+        assert_eq!(strip_query("/mic?serial=S201200586CQK#top"), "/mic#top");
+        assert_eq!(strip_query("/mic#top"), "/mic#top");
+
+        let config = Config {
+            last_route: Some(String::from("/?serial=S201200586CQK")),
+            ..Default::default()
+        };
+        assert!(!redact_config(config).to_string().contains("S201200586CQK"));
+    }
+
+    #[test]
+    fn leaves_other_values_alone() {
+        let status = json!({"Status": {"config": {"http_settings": {"port": 14564}}}});
+        assert_eq!(redact(&status), status);
+    }
+}
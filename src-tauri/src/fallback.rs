@@ -0,0 +1,213 @@
+use crate::config::Config;
+use crate::{ask_user, devices, MonitorState};
+#[cfg(target_os = "linux")]
+use crate::{readiness, watchdog, WINDOW_NAME};
+use log::{error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+#[cfg(target_os = "linux")]
+use tauri::WebviewWindow;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+/// Tracks whether we're running without a webview, and sending the user to their browser
+#[derive(Default)]
+pub struct FallbackState {
+    /// Set once the webview has failed, from this point on we run without any windows
+    active: AtomicBool,
+
+    /// Whether the user has asked for the browser to be opened, but we don't have an address yet
+    open_pending: AtomicBool,
+
+    /// Whether the user has agreed to the browser being used this session
+    accepted: AtomicBool,
+
+    /// Whether we're currently asking the user, so repeated requests don't stack up dialogs
+    asking: AtomicBool,
+}
+
+impl FallbackState {
+    pub fn is_active(&self) -> bool {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Returns true if this call switched to the browser, false if we already had
+    fn activate(&self) -> bool {
+        !self.active.swap(true, Ordering::Relaxed)
+    }
+
+    /// Returns false if the user is already being asked
+    fn start_asking(&self) -> bool {
+        !self.asking.swap(true, Ordering::Relaxed)
+    }
+
+    fn finish_asking(&self, accepted: bool) {
+        self.accepted.store(accepted, Ordering::Relaxed);
+        self.asking.store(false, Ordering::Relaxed);
+    }
+
+    fn is_accepted(&self) -> bool {
+        self.accepted.load(Ordering::Relaxed)
+    }
+
+    fn set_open_pending(&self) {
+        self.open_pending.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether an open was pending, clearing it
+    fn take_open_pending(&self) -> bool {
+        self.open_pending.swap(false, Ordering::Relaxed)
+    }
+}
+
+pub fn is_active(handle: &AppHandle) -> bool {
+    handle.state::<FallbackState>().is_active()
+}
+
+/// Called when the webview is unusable, the windows are removed but the monitor and tray are left
+/// running, and any future requests to show the UI are sent to the system browser instead.
+pub fn enable(handle: &AppHandle, reason: &str) {
+    if !handle.state::<FallbackState>().activate() {
+        return;
+    }
+
+    error!("{}, falling back to the system browser", reason);
+
+    // Let the next launch know, so it can try to work around whatever went wrong.
+    #[cfg(target_os = "linux")]
+    crate::renderer::record_crash();
+
+    // The overlays are separate from the UI, and can carry on as they are.
+    for window in devices::get_ui_windows(handle) {
+        let _ = window.destroy();
+    }
+}
+
+/// Called when the webview process terminates, if it never managed to load the UI, it's unlikely
+/// that it ever will, so switch to the browser, otherwise simply reload it.
+#[cfg(target_os = "linux")]
+fn renderer_terminated(handle: &AppHandle, reason: &str) {
+    if readiness::has_loaded(handle) {
+        watchdog::recover(handle, reason);
+        return;
+    }
+
+    // The window may already have been requested, in which case the user is waiting for it.
+    let visible = handle
+        .get_webview_window(WINDOW_NAME)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+
+    enable(handle, reason);
+    if visible {
+        tokio::task::spawn(request_open(handle.clone()));
+    }
+}
+
+/// Watches for the webview's web process going away (webkit2gtk will simply leave a blank window)
+#[cfg(target_os = "linux")]
+pub fn watch_renderer(window: &WebviewWindow) {
+    use webkit2gtk::WebViewExt;
+
+    let handle = window.app_handle().clone();
+    let _ = window.with_webview(move |webview| {
+        webview
+            .inner()
+            .connect_web_process_terminated(move |_, reason| {
+                let reason = format!("The webview's renderer terminated ({:?})", reason);
+                renderer_terminated(&handle, &reason);
+            });
+    });
+}
+
+/// Called when something has asked for the UI to be shown
+pub async fn request_open(handle: AppHandle) {
+    let state = handle.state::<FallbackState>();
+    if !state.is_accepted() {
+        let automatic = handle
+            .state::<Mutex<Config>>()
+            .lock()
+            .unwrap()
+            .browser_fallback;
+        if !state.start_asking() {
+            return;
+        }
+        let accepted = automatic || {
+            let title = String::from("GoXLR Utility UI");
+            let message = String::from(
+                "The GoXLR Utility UI was unable to display its window.\n\nWould you like to \
+                open the GoXLR Utility in your web browser instead?",
+            );
+            ask_user(&handle, title, message).await
+        };
+        state.finish_asking(accepted);
+        if !accepted {
+            info!("Browser fallback declined");
+            return;
+        }
+    }
+
+    let address = handle
+        .state::<MonitorState>()
+        .address
+        .lock()
+        .unwrap()
+        .clone();
+    match address {
+        Some(address) => open(&handle, &address),
+        None => {
+            info!("Utility not ready yet, the browser will be opened once it is");
+            state.set_open_pending();
+        }
+    }
+}
+
+/// Called when the Utility becomes ready, opens the browser if it was previously requested
+pub fn on_ready(handle: &AppHandle, address: &str) {
+    if handle.state::<FallbackState>().take_open_pending() {
+        open(handle, address);
+    }
+}
+
+fn open(handle: &AppHandle, address: &str) {
+    info!("Opening {} in the system browser", address);
+    if let Err(e) = handle.opener().open_url(address, None::<&str>) {
+        warn!("Unable to open the system browser: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activates_once() {
+        let state = FallbackState::default();
+        assert!(!state.is_active());
+        assert!(state.activate());
+        assert!(!state.activate());
+        assert!(state.is_active());
+    }
+
+    #[test]
+    fn only_asks_once_at_a_time() {
+        let state = FallbackState::default();
+        assert!(state.start_asking());
+        assert!(!state.start_asking());
+
+        state.finish_asking(false);
+        assert!(!state.is_accepted());
+        assert!(state.start_asking());
+        state.finish_asking(true);
+        assert!(state.is_accepted());
+    }
+
+    #[test]
+    fn pending_open_is_taken_once() {
+        let state = FallbackState::default();
+        assert!(!state.take_open_pending());
+        state.set_open_pending();
+        assert!(state.take_open_pending());
+        assert!(!state.take_open_pending());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::ipc;
+use log::{info, warn};
+use serde_json::Value;
+use std::path::PathBuf;
+
+/// The Utility's data directories, as reported in the `paths` section of its status
+#[derive(Debug, Copy, Clone)]
+pub enum Folder {
+    Profiles,
+    MicProfiles,
+    Samples,
+    Presets,
+    Icons,
+    Logs,
+}
+
+impl Folder {
+    pub const ALL: [Folder; 6] = [
+        Folder::Profiles,
+        Folder::MicProfiles,
+        Folder::Samples,
+        Folder::Presets,
+        Folder::Icons,
+        Folder::Logs,
+    ];
+
+    /// The name used to refer to this folder from the command line, tray and UI
+    pub fn name(&self) -> &'static str {
+        match self {
+            Folder::Profiles => "profiles",
+            Folder::MicProfiles => "mic-profiles",
+            Folder::Samples => "samples",
+            Folder::Presets => "presets",
+            Folder::Icons => "icons",
+            Folder::Logs => "logs",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Folder> {
+        Folder::ALL.into_iter().find(|folder| folder.name() == name)
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Folder::Profiles => "Profiles",
+            Folder::MicProfiles => "Mic Profiles",
+            Folder::Samples => "Samples",
+            Folder::Presets => "Presets",
+            Folder::Icons => "Icons",
+            Folder::Logs => "Logs",
+        }
+    }
+
+    fn path_key(&self) -> &'static str {
+        match self {
+            Folder::Profiles => "profile_directory",
+            Folder::MicProfiles => "mic_profile_directory",
+            Folder::Samples => "samples_directory",
+            Folder::Presets => "presets_directory",
+            Folder::Icons => "icons_directory",
+            Folder::Logs => "logs_directory",
+        }
+    }
+}
+
+/// Opens one of the Utility's data directories in the system's file manager. The location is
+/// fetched from the Utility each time, as it may have been changed in its settings.
+pub async fn open(folder: Folder) -> Result<(), String> {
+    let path = get_path(folder).await?;
+    info!("Opening {} folder: {}", folder.name(), path.display());
+    tauri_plugin_opener::open_path(&path, None::<&str>).map_err(|e| {
+        warn!("Unable to open {}: {}", path.display(), e);
+        format!("Unable to open {}: {}", path.display(), e)
+    })
+}
+
+/// Handles `open <folder>` on the command line
+pub async fn open_from_args(name: Option<&String>) -> Result<(), String> {
+    let names: Vec<&str> = Folder::ALL.iter().map(Folder::name).collect();
+    let Some(name) = name else {
+        return Err(format!("Usage: open <{}>", names.join("|")));
+    };
+    let Some(folder) = Folder::from_name(name) else {
+        return Err(format!(
+            "Unknown folder '{}', expected one of: {}",
+            name,
+            names.join(", ")
+        ));
+    };
+    open(folder).await
+}
+
+async fn get_path(folder: Folder) -> Result<PathBuf, String> {
+    let result = ipc::get_status()
+        .await
+        .map_err(|e| format!("Unable to fetch the status from the GoXLR Utility: {}", e))?;
+    if let Some(paths) = result.get("Status").and_then(|status| status.get("paths")) {
+        if let Some(path) = paths.get(folder.path_key()).and_then(Value::as_str) {
+            return Ok(PathBuf::from(path));
+        }
+    }
+    Err(format!(
+        "The GoXLR Utility didn't report its {} folder",
+        folder.name()
+    ))
+}
@@ -0,0 +1,222 @@
+use crate::state::StateStore;
+use crate::{ask_user, tell_user, MonitorState};
+use log::{info, warn};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use tauri::{AppHandle, Manager};
+
+static TITLE: &str = "GoXLR Utility";
+
+/// Extensions the Utility's sampler is able to play
+static SAMPLE_EXTENSIONS: [&str; 4] = ["wav", "mp3", "ogg", "flac"];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FileType {
+    Sample,
+    Profile,
+    MicProfile,
+}
+
+impl FileType {
+    fn from_path(path: &Path) -> Option<Self> {
+        if !path.is_file() {
+            return None;
+        }
+        Self::from_extension(path)
+    }
+
+    /// Classifies a file by its extension alone, without checking it exists
+    fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "goxlr" => Some(Self::Profile),
+            "goxlrmicprofile" => Some(Self::MicProfile),
+            extension if SAMPLE_EXTENSIONS.contains(&extension) => Some(Self::Sample),
+            _ => None,
+        }
+    }
+
+    /// The key of this type's directory, in the `paths` section of the Utility's status
+    fn path_key(&self) -> &'static str {
+        match self {
+            Self::Sample => "samples_directory",
+            Self::Profile => "profile_directory",
+            Self::MicProfile => "mic_profile_directory",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Self::Sample => "samples",
+            Self::Profile => "profiles",
+            Self::MicProfile => "mic profiles",
+        }
+    }
+}
+
+/// Called when files are dropped onto a window, copies any we recognise into the Utility's
+/// matching data directory.
+pub async fn import_files(handle: AppHandle, files: Vec<PathBuf>) {
+    let Some(status) = handle.state::<StateStore>().get_status() else {
+        let message = "Files can't be imported while the GoXLR Utility isn't connected";
+        tell_user(&handle, TITLE.into(), message.into()).await;
+        return;
+    };
+
+    let mut imported = 0;
+    let mut problems = vec![];
+    for file in files {
+        let name = file
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let Some(file_type) = FileType::from_path(&file) else {
+            problems.push(format!("{}: Not a sample, profile or mic profile", name));
+            continue;
+        };
+
+        match import_file(&handle, &status, &file, file_type).await {
+            Ok(true) => imported += 1,
+            Ok(false) => {}
+            Err(e) => {
+                warn!("Unable to import {}: {}", file.display(), e);
+                problems.push(format!("{}: {}", name, e));
+            }
+        }
+    }
+
+    // The Utility watches these directories itself, but make sure we're up to date.
+    if imported > 0 {
+        handle
+            .state::<MonitorState>()
+            .refresh
+            .store(true, Ordering::Relaxed);
+    }
+
+    if !problems.is_empty() {
+        let message = format!(
+            "Some files couldn't be imported:\n\n{}",
+            problems.join("\n")
+        );
+        tell_user(&handle, TITLE.into(), message).await;
+    }
+}
+
+/// Copies a single file, returning whether it was actually imported
+async fn import_file(
+    handle: &AppHandle,
+    status: &Value,
+    file: &Path,
+    file_type: FileType,
+) -> Result<bool, String> {
+    let directory = get_directory(status, file_type)?;
+    let name = file.file_name().ok_or("Invalid file name")?;
+    let target = directory.join(name);
+
+    // Dropping a file from the directory it's already in, nothing to do.
+    if target == file {
+        return Ok(false);
+    }
+
+    if target.exists() {
+        let message = format!(
+            "{} already exists in your {}, do you want to replace it?",
+            name.to_string_lossy(),
+            file_type.description()
+        );
+        if !ask_user(handle, TITLE.into(), message).await {
+            return Ok(false);
+        }
+    }
+
+    tokio::fs::create_dir_all(&directory)
+        .await
+        .map_err(|e| format!("Unable to create {}: {}", directory.display(), e))?;
+    tokio::fs::copy(file, &target)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    info!("Imported {} to {}", file.display(), target.display());
+    Ok(true)
+}
+
+fn get_directory(status: &Value, file_type: FileType) -> Result<PathBuf, String> {
+    if let Some(paths) = status.get("paths") {
+        if let Some(path) = paths.get(file_type.path_key()).and_then(Value::as_str) {
+            return Ok(PathBuf::from(path));
+        }
+    }
+    Err(format!(
+        "The GoXLR Utility didn't report its {} directory",
+        file_type.description()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    static STATUS: &str = include_str!("fixtures/status.json");
+
+    #[test]
+    fn classifies_by_extension() {
+        let classify = |name: &str| FileType::from_extension(Path::new(name));
+        assert_eq!(classify("Streaming.goxlr"), Some(FileType::Profile));
+        assert_eq!(
+            classify("Voice.goxlrMicProfile"),
+            Some(FileType::MicProfile)
+        );
+        assert_eq!(classify("Airhorn.wav"), Some(FileType::Sample));
+        assert_eq!(classify("Airhorn.MP3"), Some(FileType::Sample));
+        assert_eq!(classify("Airhorn.flac"), Some(FileType::Sample));
+    }
+
+    #[test]
+    fn rejects_unknown_files() {
+        let classify = |name: &str| FileType::from_extension(Path::new(name));
+        assert_eq!(classify("notes.txt"), None);
+        assert_eq!(classify("goxlr"), None);
+        assert_eq!(classify("Streaming.goxlr.bak"), None);
+    }
+
+    #[test]
+    fn only_imports_files() {
+        let directory = std::env::temp_dir().join("goxlr-import-test.goxlr");
+        std::fs::create_dir_all(&directory).unwrap();
+        assert_eq!(FileType::from_path(&directory), None);
+
+        let file = directory.join("Streaming.goxlr");
+        std::fs::write(&file, "").unwrap();
+        assert_eq!(FileType::from_path(&file), Some(FileType::Profile));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn finds_directories_in_status() {
+        let status: Value = serde_json::from_str(STATUS).unwrap();
+        assert_eq!(
+            get_directory(&status, FileType::Sample),
+            Ok(PathBuf::from(
+                "/home/user/.local/share/goxlr-utility/samples"
+            ))
+        );
+        assert_eq!(
+            get_directory(&status, FileType::MicProfile),
+            Ok(PathBuf::from(
+                "/home/user/.local/share/goxlr-utility/mic-profiles"
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_directory_is_an_error() {
+        let status = json!({"paths": {"profile_directory": "/profiles"}});
+        assert!(get_directory(&status, FileType::Profile).is_ok());
+        assert!(get_directory(&status, FileType::Sample).is_err());
+        assert!(get_directory(&json!({}), FileType::Profile).is_err());
+    }
+}
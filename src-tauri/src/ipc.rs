@@ -2,21 +2,187 @@ use futures::{SinkExt, TryStreamExt};
 use interprocess::local_socket::tokio::prelude::LocalSocketStream;
 use interprocess::local_socket::tokio::{RecvHalf, SendHalf};
 use interprocess::local_socket::traits::tokio::Stream;
+use interprocess::local_socket::{GenericFilePath, GenericNamespaced, ToFsName, ToNsName};
+use log::{debug, log_enabled, Level};
 use serde::{Deserialize, Serialize};
-use std::io::Error;
+use serde_json::{json, Value};
+use std::io::{Error, ErrorKind};
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio_serde::formats::SymmetricalJson;
 use tokio_serde::SymmetricallyFramed;
 use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
 
+static SOCKET_PATH: &str = "/tmp/goxlr.socket";
+static NAMED_PIPE: &str = "@goxlr.socket";
+
+/// The name of the Utility's executable, which should be on the other end of the socket
+#[cfg(target_os = "linux")]
+static DAEMON_BINARY: &str = "goxlr-daemon";
+
+/// Permits connecting to a Utility owned by a different user
+static ALLOW_FOREIGN_DAEMON: AtomicBool = AtomicBool::new(false);
+
+pub fn set_allow_foreign_daemon(allow: bool) {
+    ALLOW_FOREIGN_DAEMON.store(allow, Ordering::Relaxed);
+}
+
+/// Returns the location of the socket on this platform
+pub fn get_socket_path() -> &'static str {
+    if cfg!(windows) {
+        NAMED_PIPE
+    } else {
+        SOCKET_PATH
+    }
+}
+
+/// Connects to the GoXLR Utility's IPC socket
+pub async fn connect() -> Result<LocalSocketStream, Error> {
+    // Because Windows also supports unix sockets, we need to maintain legacy behaviour..
+    let path = if cfg!(windows) {
+        NAMED_PIPE.to_ns_name::<GenericNamespaced>()
+    } else {
+        SOCKET_PATH.to_fs_name::<GenericFilePath>()
+    }?;
+
+    let stream = LocalSocketStream::connect(path).await?;
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    let stream = if ALLOW_FOREIGN_DAEMON.load(Ordering::Relaxed) {
+        stream
+    } else {
+        verify_peer(stream)?
+    };
+
+    Ok(stream)
+}
+
+/// The socket lives in /tmp, so anyone could have created it. Before we trust anything it tells
+/// us, make sure both the socket and the process on the other end belong to the current user.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn verify_peer(stream: LocalSocketStream) -> Result<LocalSocketStream, Error> {
+    use interprocess::os::unix::uds_local_socket::tokio::Stream as UdStream;
+    use nix::unistd::getuid;
+    use std::os::fd::OwnedFd;
+    use std::os::unix::fs::{FileTypeExt, MetadataExt};
+
+    let uid = getuid().as_raw();
+    let denied = |message: String| {
+        let hint = "If this is intentional, launch with --allow-foreign-daemon";
+        Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{}. {}", message, hint),
+        )
+    };
+
+    let metadata = std::fs::symlink_metadata(SOCKET_PATH)?;
+    if !metadata.file_type().is_socket() {
+        return Err(denied(format!("{} is not a socket", SOCKET_PATH)));
+    }
+    if metadata.uid() != uid {
+        let message = format!(
+            "The GoXLR Utility socket at {} is owned by another user (uid {})",
+            SOCKET_PATH,
+            metadata.uid()
+        );
+        return Err(denied(message));
+    }
+
+    let LocalSocketStream::UdSocket(stream) = stream;
+    let fd = OwnedFd::try_from(stream)?;
+
+    let (peer_uid, peer_pid) = get_peer_credentials(&fd)?;
+    if peer_uid != uid {
+        let message = format!(
+            "The GoXLR Utility is running as another user (uid {})",
+            peer_uid
+        );
+        return Err(denied(message));
+    }
+
+    // Where we can, make sure it's actually the Utility on the other end of the socket..
+    if let Some(pid) = peer_pid {
+        verify_process(pid).map_err(denied)?;
+    }
+
+    Ok(LocalSocketStream::from(UdStream::try_from(fd)?))
+}
+
+#[cfg(target_os = "linux")]
+fn get_peer_credentials(fd: &std::os::fd::OwnedFd) -> Result<(u32, Option<i32>), Error> {
+    use nix::sys::socket::{getsockopt, sockopt::PeerCredentials};
+
+    let credentials = getsockopt(fd, PeerCredentials)?;
+    Ok((credentials.uid(), Some(credentials.pid())))
+}
+
+#[cfg(target_os = "macos")]
+fn get_peer_credentials(fd: &std::os::fd::OwnedFd) -> Result<(u32, Option<i32>), Error> {
+    use nix::sys::socket::{getsockopt, sockopt::LocalPeerPid};
+    use nix::unistd::getpeereid;
+
+    let (uid, _) = getpeereid(fd)?;
+    Ok((uid.as_raw(), getsockopt(fd, LocalPeerPid).ok()))
+}
+
+#[cfg(target_os = "linux")]
+fn verify_process(pid: i32) -> Result<(), String> {
+    let path = std::fs::read_link(format!("/proc/{}/exe", pid))
+        .map_err(|e| format!("Unable to identify the process behind the socket: {}", e))?;
+    debug!("GoXLR Utility running as {} (pid {})", path.display(), pid);
+
+    if !is_daemon_path(&path) {
+        let message = format!(
+            "The process behind the socket ({}) is not the GoXLR Utility",
+            path.display()
+        );
+        return Err(message);
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn verify_process(pid: i32) -> Result<(), String> {
+    // There's no /proc to find the executable with here, so the uid checks will have to do.
+    debug!("GoXLR Utility running with pid {}", pid);
+    Ok(())
+}
+
+/// Checks an executable is the Utility's daemon. If it's been updated while running, the
+/// kernel will report the old binary as '(deleted)', which is still fine.
+#[cfg(target_os = "linux")]
+fn is_daemon_path(path: &std::path::Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.trim_end_matches(" (deleted)") == DAEMON_BINARY
+}
+
+/// Asks the Utility for its current status, over an already connected socket
+pub async fn request_status(socket: &mut Socket<Value, Value>) -> Result<Value, Error> {
+    socket.send(json!("GetStatus")).await?;
+    socket
+        .try_read()
+        .await?
+        .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "No response from the Utility"))
+}
+
+/// Connects to the Utility, and fetches its current status
+pub async fn get_status() -> Result<Value, Error> {
+    let mut socket = Socket::new(connect().await?);
+    request_status(&mut socket).await
+}
+
 /// This is brought in from the goxlr-ipc crate, we ultimately don't care about the IPC format
 /// for requests / responses, and simply want to handle serde_json's 'Value' type, so it might
 /// be useful to fix this so that the ipc inherits are optional. Until then, we'll simply copypasta.
+///
+/// Responses are decoded here rather than by a framed reader, so they can be summarised in the
+/// log without having to be serialised again.
 #[derive(Debug)]
 pub struct Socket<In, Out> {
-    reader:
-        SymmetricallyFramed<FramedRead<RecvHalf, LengthDelimitedCodec>, In, SymmetricalJson<In>>,
+    reader: FramedRead<RecvHalf, LengthDelimitedCodec>,
     writer:
         SymmetricallyFramed<FramedWrite<SendHalf, LengthDelimitedCodec>, Out, SymmetricalJson<Out>>,
+    response: PhantomData<In>,
 }
 
 impl<In, Out> Socket<In, Out>
@@ -27,20 +193,79 @@ where
     // This is basically identical to the existing one, except we take an interprocess LocalSocketStream instead..
     pub fn new(stream: LocalSocketStream) -> Self {
         let (stream_read, stream_write) = stream.split();
-        let length_delimited_read = FramedRead::new(stream_read, LengthDelimitedCodec::new());
-        let reader = SymmetricallyFramed::new(length_delimited_read, SymmetricalJson::default());
+        let reader = FramedRead::new(stream_read, LengthDelimitedCodec::new());
 
         let length_delimited_write = FramedWrite::new(stream_write, LengthDelimitedCodec::new());
         let writer = SymmetricallyFramed::new(length_delimited_write, SymmetricalJson::default());
 
-        Self { reader, writer }
+        Self {
+            reader,
+            writer,
+            response: PhantomData,
+        }
     }
 
     pub async fn try_read(&mut self) -> Result<Option<In>, Error> {
-        self.reader.try_next().await
+        let Some(frame) = self.reader.try_next().await? else {
+            return Ok(None);
+        };
+        if log_enabled!(Level::Debug) {
+            if let Ok(value) = serde_json::from_slice::<Value>(&frame) {
+                debug!("IPC Response: {}", summarise(&value));
+            }
+        }
+        let response =
+            serde_json::from_slice(&frame).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        Ok(Some(response))
     }
 
     pub async fn send(&mut self, out: Out) -> Result<(), Error> {
+        if log_enabled!(Level::Debug) {
+            if let Ok(value) = serde_json::to_value(&out) {
+                debug!("IPC Request: {}", summarise(&value));
+            }
+        }
         self.writer.send(out).await
     }
 }
+
+/// Produces a short description of an IPC message, rather than dumping the whole thing (the
+/// status response in particular is rather large).
+fn summarise(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Object(map) if map.len() == 1 => {
+            let (key, value) = map.iter().next().unwrap();
+            match value {
+                Value::Object(inner) if inner.len() == 1 => {
+                    format!("{}::{}", key, inner.keys().next().unwrap())
+                }
+                Value::String(string) => format!("{}: {}", key, string),
+                _ => key.clone(),
+            }
+        }
+        Value::Object(map) => map.keys().cloned().collect::<Vec<_>>().join(", "),
+        _ => value.to_string(),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn accepts_the_daemon() {
+        assert!(is_daemon_path(Path::new("/usr/bin/goxlr-daemon")));
+        assert!(is_daemon_path(Path::new(
+            "/home/user/.local/bin/goxlr-daemon (deleted)"
+        )));
+    }
+
+    #[test]
+    fn rejects_other_processes() {
+        assert!(!is_daemon_path(Path::new("/usr/bin/python3")));
+        assert!(!is_daemon_path(Path::new("/tmp/goxlr-daemon-fake")));
+        assert!(!is_daemon_path(Path::new("/")));
+    }
+}
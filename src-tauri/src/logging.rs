@@ -0,0 +1,64 @@
+use crate::config::get_data_dir;
+use flexi_logger::{
+    detailed_format, Cleanup, Criterion, Duplicate, FileSpec, Logger, LoggerHandle, Naming,
+};
+use std::path::PathBuf;
+
+static LOG_BASENAME: &str = "goxlr-utility-ui";
+static LOG_FILE_SIZE: u64 = 1024 * 1024;
+static LOG_FILE_COUNT: usize = 5;
+
+/// Sets up logging to a rotating log file in the app's data directory. By default we log at
+/// `info`, this can be overridden with the `RUST_LOG` environment variable, or by `--verbose`.
+///
+/// The returned handle needs to be kept alive for as long as we want to keep logging.
+pub fn init(verbose: bool) -> Option<LoggerHandle> {
+    let logger = if verbose {
+        Logger::try_with_str("debug")
+    } else {
+        Logger::try_with_env_or_str("info")
+    };
+
+    let mut logger = match logger {
+        Ok(logger) => logger.format(detailed_format),
+        Err(e) => {
+            eprintln!("Unable to configure logging: {}", e);
+            return None;
+        }
+    };
+
+    // If we can't find somewhere to put the file, we'll just stick to the console..
+    if let Some(dir) = get_log_dir() {
+        let spec = FileSpec::default()
+            .directory(dir)
+            .basename(LOG_BASENAME)
+            .suppress_timestamp();
+
+        logger = logger
+            .log_to_file(spec)
+            .append()
+            .rotate(
+                Criterion::Size(LOG_FILE_SIZE),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(LOG_FILE_COUNT),
+            )
+            .duplicate_to_stderr(Duplicate::All);
+    }
+
+    match logger.start() {
+        Ok(handle) => Some(handle),
+        Err(e) => {
+            eprintln!("Unable to start logging: {}", e);
+            None
+        }
+    }
+}
+
+pub fn get_log_dir() -> Option<PathBuf> {
+    get_data_dir().map(|dir| dir.join("logs"))
+}
+
+/// Returns the file currently being logged to
+pub fn get_log_file() -> Option<PathBuf> {
+    get_log_dir().map(|dir| dir.join(format!("{}_rCURRENT.log", LOG_BASENAME)))
+}
@@ -1,8 +1,8 @@
 use objc2::rc::Retained;
 use objc2::AllocAnyThread;
 use objc2_app_kit::{
-    NSAlert, NSApplication, NSApplicationActivationPolicy, NSCriticalAlertStyle, NSImage,
-    NSInformationalAlertStyle, NSWindowLevel,
+    NSAlert, NSApplication, NSApplicationActivationPolicy, NSControlStateValueOn,
+    NSCriticalAlertStyle, NSImage, NSInformationalAlertStyle, NSWindowLevel,
 };
 use objc2_foundation::{NSData, NSString};
 
@@ -75,3 +75,40 @@ pub fn show_question(mtm: MainThreadMarker, title: String, content: String) -> R
     }
     Err(())
 }
+
+/// Asks whether to keep running or quit, with a 'Don't ask again' checkbox
+pub fn show_close_question(
+    mtm: MainThreadMarker,
+    title: String,
+    content: String,
+) -> Option<(bool, bool)> {
+    unsafe {
+        let alert = NSAlert::new(mtm);
+        alert.setIcon(get_icon().as_deref());
+        alert.addButtonWithTitle(&NSString::from_str("Keep Running"));
+        alert.addButtonWithTitle(&NSString::from_str("Quit"));
+        alert.addButtonWithTitle(&NSString::from_str("Cancel"));
+        alert.setMessageText(&NSString::from_str(&title));
+        alert.setInformativeText(&NSString::from_str(&content));
+        alert.setAlertStyle(NSInformationalAlertStyle);
+        alert.setShowsSuppressionButton(true);
+
+        let suppression = alert.suppressionButton();
+        if let Some(button) = &suppression {
+            button.setTitle(&NSString::from_str("Don't ask again"));
+        }
+
+        // Get the Window..
+        let window = alert.window();
+        window.setLevel(NSWindowLevel::from(10u8));
+
+        // Send the Alert..
+        let result = alert.runModal() as usize;
+        let remember = suppression.is_some_and(|button| button.state() == NSControlStateValueOn);
+        match result {
+            1000 => Some((true, remember)),
+            1001 => Some((false, remember)),
+            _ => None,
+        }
+    }
+}
@@ -4,41 +4,100 @@
 #[cfg(target_os = "macos")]
 mod macos;
 
+mod commands;
+mod config;
+mod crash;
+mod devices;
+mod diagnose;
+mod fallback;
+mod folders;
+mod import;
 mod ipc;
+mod logging;
+mod memory;
+mod navigation;
+mod notifications;
+mod osd;
+mod overlay;
+mod pages;
+mod readiness;
+#[cfg(target_os = "linux")]
+mod renderer;
+mod startup;
+mod state;
+mod title;
+mod tray;
+mod watchdog;
 
 use directories::ProjectDirs;
-use interprocess::local_socket::tokio::prelude::LocalSocketStream;
-use interprocess::local_socket::{GenericFilePath, GenericNamespaced, ToFsName, ToNsName};
+use log::{debug, error, info, warn};
 use serde_json::{json, Value};
 use std::env;
 use std::fs::{create_dir_all, File};
 use std::io::ErrorKind;
+use std::net::{IpAddr, TcpStream, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
+use crate::config::{CloseBehaviour, Config};
+use crate::devices::DeviceState;
+use crate::fallback::FallbackState;
 use crate::ipc::Socket;
-use interprocess::local_socket::traits::tokio::Stream;
+use crate::memory::MemoryState;
+use crate::navigation::CapabilityState;
+use crate::notifications::NotificationState;
+use crate::readiness::PageState;
+use crate::startup::StartupState;
+use crate::state::StateStore;
+use crate::watchdog::WatchdogState;
 use std::path::{Path, PathBuf};
-use tauri::{AppHandle, Emitter, Listener, Manager};
-use tungstenite::{connect, Message};
+use tauri::{
+    AppHandle, DragDropEvent, Emitter, Listener, Manager, RunEvent, Url, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder, Window,
+};
+use tauri_plugin_window_state::{AppHandleExt, StateFlags};
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{connect, Message, WebSocket};
 
 static WINDOW_NAME: &str = "main";
 static READY_EVENT_NAME: &str = "READY";
 static SHOW_EVENT_NAME: &str = "si-event";
+static SHOW_DEVICE_EVENT_NAME: &str = "SHOW-DEVICE";
 static HIDE_EVENT_NAME: &str = "HIDE-UI";
 static STOP_EVENT_NAME: &str = "seppuku";
+static QUIT_EVENT_NAME: &str = "QUIT";
+
+static UTILITY_SCRIPT: &str = include_str!("utility.js");
 
-static SOCKET_PATH: &str = "/tmp/goxlr.socket";
-static NAMED_PIPE: &str = "@goxlr.socket";
+static RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Used to tell the monitor that we're quitting, so it can cleanly close the websocket
+#[derive(Default)]
+struct MonitorState {
+    shutdown: AtomicBool,
+    connected: AtomicBool,
+    /// Set to have the monitor request a fresh status from the Utility
+    refresh: AtomicBool,
+    address: Mutex<Option<String>>,
+}
 
 #[tokio::main]
 async fn main() -> Result<(), String> {
-    // Under Wayland on Linux gtkwebkit has a bug which prevents it from launching correctly, the
-    // following sets an ENV flag prior to startup that disables the DMABUF renderer to fix it.
+    // This needs to stay alive until we exit, otherwise logging stops.
+    let _logger = logging::init(env::args().any(|arg| arg == "--verbose"));
+    info!("Starting GoXLR Utility UI {}", env!("CARGO_PKG_VERSION"));
+    crash::install_panic_hook();
+
+    // webkit2gtk has a handful of bugs which can prevent it from launching correctly on Linux,
+    // the workarounds are applied via the environment, so need to be set prior to startup.
     #[cfg(target_os = "linux")]
-    env::set_var("WEBKIT_DISABLE_DMABUF_RENDERER", "1");
+    renderer::configure(&Config::load());
 
     // If running the utility has an error, make sure log level is debug, and propagate the
     // error up to the user on Windows.
     if let Err(e) = run_application().await {
+        error!("{}", e);
         show_error("GoXLR Utility UI".into(), e.to_string());
         return Err(e.to_string());
     }
@@ -46,142 +105,510 @@ async fn main() -> Result<(), String> {
 }
 
 async fn run_application() -> Result<(), String> {
+    let started = Instant::now();
     let args: Vec<String> = env::args().collect();
-    if args.len() == 2 {
-        if args[1] == "--install" {
-            manage(true).await?;
-            return Ok(());
-        }
-        if args[1] == "--remove" {
-            manage(false).await?;
-            return Ok(());
+    let config = Config::load();
+
+    // Permits setups where the Utility is deliberately being run as a different user
+    let allow_foreign = has_flag(&args, "--allow-foreign-daemon") || config.allow_foreign_daemon;
+    ipc::set_allow_foreign_daemon(allow_foreign);
+
+    if has_flag(&args, "--install") {
+        manage(true).await?;
+        return Ok(());
+    }
+    if has_flag(&args, "--remove") {
+        manage(false).await?;
+        return Ok(());
+    }
+    if has_flag(&args, "--diagnose") {
+        // An optional path can be provided for the report..
+        let output = args
+            .iter()
+            .skip_while(|arg| *arg != "--diagnose")
+            .nth(1)
+            .filter(|arg| !arg.starts_with("--"))
+            .map(PathBuf::from);
+
+        let path = diagnose::run(output).await?;
+        info!("Diagnostics written to {}", path.display());
+
+        let title = String::from("GoXLR Utility UI");
+        let message = format!(
+            "A diagnostics report has been written to:\n{}\n\nOpen the containing folder?",
+            path.display()
+        );
+        if show_option(title, message).is_ok() {
+            if let Err(e) = tauri_plugin_opener::reveal_item_in_dir(&path) {
+                warn!("Unable to open folder: {}", e);
+            }
         }
+        return Ok(());
+    }
+    if args.get(1).is_some_and(|arg| arg == "open") {
+        // Opens one of the Utility's folders (eg. `open samples`) without starting the UI
+        return folders::open_from_args(args.get(2)).await;
     }
 
-    let url = goxlr_preflight().await?;
+    // When launched hidden (either by flag, or by preference) we do all the usual work, and load
+    // the UI in the background, but don't show the window until something asks for it.
+    let hidden = has_flag(&args, "--hidden") || config.start_hidden;
+    let show_tray = config.show_tray;
+    let zoom = config.zoom;
 
+    // The Utility is checked once the window is up, so there's something to look at meanwhile.
     let builder = tauri::Builder::default();
     builder
+        .manage(Mutex::new(config))
+        .manage(MonitorState::default())
+        .manage(DeviceState::default())
+        .manage(CapabilityState::default())
+        .manage(PageState::default())
+        .manage(WatchdogState::default())
+        .manage(StartupState::new(started))
+        .manage(StateStore::default())
+        .manage(FallbackState::default())
+        .manage(MemoryState::default())
+        .manage(NotificationState::default())
         .plugin(tauri_plugin_single_instance::init(|app, _, _| {
             // Register the Single Instance plugin on Windows, Linux and MacOs
             // Trigger a global event if something (eg, the util) attempts to open this again.
             let _ = app.emit(SHOW_EVENT_NAME, None::<String>);
         }))
-        .plugin(tauri_plugin_window_state::Builder::default().build())
+        .plugin(
+            tauri_plugin_window_state::Builder::default()
+                .with_state_flags(window_state_flags())
+                .with_denylist(&[overlay::OVERLAY_WINDOW_NAME, osd::OSD_WINDOW_NAME])
+                .build(),
+        )
         .plugin(tauri_plugin_opener::init())
-        .setup(|app| {
+        .invoke_handler(tauri::generate_handler![
+            commands::report_route,
+            commands::startup_progress,
+            commands::heartbeat,
+            commands::zoom,
+            commands::retry,
+            commands::open_in_browser,
+            commands::view_logs,
+            commands::open_folder,
+            commands::quit
+        ])
+        .setup(move |app| {
+            // Create the main window, it starts hidden and on the loading page. If the webview
+            // can't be created, we'll carry on without it and use the system browser instead.
+            match create_main_window(app.handle()) {
+                Ok(window) => {
+                    let _ = window.set_zoom(zoom);
+                    startup::mark(app.handle(), "Window created");
+                }
+                Err(e) => {
+                    let reason = format!("Unable to create the main window: {}", e);
+                    fallback::enable(app.handle(), &reason);
+                }
+            }
+
             let global_window = app.handle().clone();
             app.listen_any(SHOW_EVENT_NAME, move |_| {
-                // Do anything and everything to make sure this Window is visible and focused!
-                let window = global_window.get_webview_window(WINDOW_NAME).unwrap();
-                let _ = window.show();
-                let _ = window.unminimize();
-                let _ = window.set_focus();
+                if redirect_show(&global_window) {
+                    return;
+                }
 
-                #[cfg(target_os = "macos")]
-                {
-                    match macos::MainThreadMarker::new() {
-                        Some(mtm) => macos::show_dock(mtm),
-                        None => {
-                            macos::Queue::main().exec_async(|| {
-                                macos::show_dock(macos::MainThreadMarker::new().unwrap())
-                            });
-                        }
-                    };
+                // Do anything and everything to make sure our Windows are visible and focused!
+                for window in devices::get_ui_windows(&global_window) {
+                    show_window(&window);
+                }
+            });
+
+            let device_handle = app.handle().clone();
+            app.listen_any(SHOW_DEVICE_EVENT_NAME, move |data| {
+                // Once restored, the device windows are recreated alongside the main window.
+                if redirect_show(&device_handle) {
+                    return;
+                }
+                if let Ok(serial) = serde_json::from_str::<String>(data.payload()) {
+                    let state = device_handle.state::<DeviceState>();
+                    let label = devices::get_window_label(&state.devices.lock().unwrap(), &serial);
+                    if let Some(window) = device_handle.get_webview_window(&label) {
+                        show_window(&window);
+                    }
                 }
             });
 
             let hide_handle = app.handle().clone();
             app.listen_any(HIDE_EVENT_NAME, move |_| {
-                let window = hide_handle.get_webview_window(WINDOW_NAME).unwrap();
-                window.hide().unwrap();
-                #[cfg(target_os = "macos")]
-                {
-                    match macos::MainThreadMarker::new() {
-                        Some(mtm) => macos::hide_dock(mtm),
-                        None => {
-                            macos::Queue::main().exec_async(|| {
-                                macos::hide_dock(macos::MainThreadMarker::new().unwrap())
-                            });
-                        }
-                    };
+                for window in devices::get_ui_windows(&hide_handle) {
+                    let _ = window.hide();
                 }
+                #[cfg(target_os = "macos")]
+                set_dock_visible(false);
             });
 
             let ready_handle = app.handle().clone();
             app.listen_any(READY_EVENT_NAME, move |data| {
-                let address = data.payload();
-                let window = ready_handle.get_webview_window(WINDOW_NAME).unwrap();
-                let _ = window.eval(format!("window.location.replace({})", address).as_str());
+                if fallback::is_active(&ready_handle) {
+                    if let Ok(address) = serde_json::from_str::<String>(data.payload()) {
+                        fallback::on_ready(&ready_handle, &address);
+                    }
+                    return;
+                }
+
+                // Head back to wherever we were last time..
+                let config = ready_handle.state::<Mutex<Config>>();
+                let (route, zoom) = {
+                    let config = config.lock().unwrap();
+                    (config.last_route.clone(), config.zoom)
+                };
+
+                let mut url = match get_utility_url(data.payload(), route.as_deref()) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        error!("Refusing to navigate to the Utility: {}", e);
+                        return;
+                    }
+                };
+
+                // The main window is for the first device, other devices have their own windows.
+                let devices = ready_handle.state::<DeviceState>();
+                let serial = devices
+                    .devices
+                    .lock()
+                    .unwrap()
+                    .first()
+                    .map(|d| d.serial.clone());
+                set_serial(&mut url, serial.as_deref());
+
+                // The window may have been released, it'll be loaded again when it's restored.
+                let Some(window) = ready_handle.get_webview_window(WINDOW_NAME) else {
+                    return;
+                };
+                if let Err(e) = window.navigate(url) {
+                    error!("Unable to navigate to the Utility: {}", e);
+                }
+                let _ = window.set_zoom(zoom);
+
+                // Make sure the page actually loads, rather than leaving the user on an error..
+                readiness::watch_page_load(&ready_handle);
+            });
+
+            let quit_handle = app.handle().clone();
+            app.listen_any(QUIT_EVENT_NAME, move |_| {
+                // Ask the monitor to close the websocket, it'll send the stop event once it's done.
+                let state = quit_handle.state::<MonitorState>();
+                if state.connected.load(Ordering::Relaxed) {
+                    state.shutdown.store(true, Ordering::Relaxed);
+                } else {
+                    // We're not connected, so there's nothing to clean up..
+                    let _ = quit_handle.emit(STOP_EVENT_NAME, None::<String>);
+                }
             });
 
             let shutdown_handle = app.handle().clone();
             app.listen_any(STOP_EVENT_NAME, move |_| {
+                // Make sure the Window State and our settings are saved before we go..
+                let _ = shutdown_handle.save_window_state(window_state_flags());
+                let config = shutdown_handle.state::<Mutex<Config>>();
+                if let Err(e) = config.lock().unwrap().save() {
+                    warn!("Unable to save settings: {}", e);
+                }
+
                 // Terminate the App..
                 shutdown_handle.exit(0);
             });
-            tokio::task::spawn(goxlr_utility_monitor(app.handle().clone(), url));
+            tokio::task::spawn(devices::device_monitor(app.handle().clone()));
+            tokio::task::spawn(notifications::run(app.handle().clone()));
+            tokio::task::spawn(overlay::run(app.handle().clone()));
+            tokio::task::spawn(osd::run(app.handle().clone()));
+            tokio::task::spawn(watchdog::run(app.handle().clone()));
+            tokio::task::spawn(memory::run(app.handle().clone()));
+
+            // Not every desktop has somewhere to put a tray icon, that's no reason not to start.
+            if show_tray {
+                if let Err(e) = tray::create_tray(app.handle()) {
+                    warn!("Unable to create the tray icon: {}", e);
+                }
+            }
+
+            // The window is created hidden, show it now unless we've been asked not to.
+            if hidden {
+                #[cfg(target_os = "macos")]
+                set_dock_visible(false);
+            } else {
+                let _ = app.emit(SHOW_EVENT_NAME, None::<String>);
+            }
 
+            // Now we can go and find the Utility..
+            tokio::task::spawn(run_startup(app.handle().clone()));
             Ok(())
         })
         .on_window_event(|window, event| {
+            if let tauri::WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) = event {
+                if devices::is_ui_window(window.label()) {
+                    let handle = window.app_handle().clone();
+                    tokio::task::spawn(import::import_files(handle, paths.clone()));
+                }
+            }
+
             if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                window.hide().unwrap();
+                // Overlays are positioned by config, and will be recreated when needed.
+                if !devices::is_ui_window(window.label()) {
+                    return;
+                }
                 api.prevent_close();
-                #[cfg(target_os = "macos")]
-                {
-                    match macos::MainThreadMarker::new() {
-                        Some(mtm) => macos::hide_dock(mtm),
-                        None => {
-                            macos::Queue::main().exec_async(|| {
-                                macos::hide_dock(macos::MainThreadMarker::new().unwrap())
-                            });
-                        }
-                    };
+
+                let config = window.app_handle().state::<Mutex<Config>>();
+                let behaviour = config.lock().unwrap().close_behaviour;
+                if behaviour != CloseBehaviour::Ask {
+                    apply_close_behaviour(window, behaviour);
+                    return;
                 }
+
+                // Dialogs block, so we can't ask from inside the event loop.
+                let window = window.clone();
+                tokio::task::spawn(async move {
+                    if let Some(behaviour) = ask_close_behaviour(window.app_handle()).await {
+                        apply_close_behaviour(&window, behaviour);
+                    }
+                });
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error running tauri app");
+        .build(tauri::generate_context!())
+        .expect("error building tauri app")
+        .run(|_, event| {
+            // Without a webview (in browser fallback, or once the UI has been released for low
+            // memory mode) we have no windows, but still need to keep running.
+            if let RunEvent::ExitRequested {
+                code: None, api, ..
+            } = event
+            {
+                api.prevent_exit();
+            }
+        });
 
     Ok(())
 }
 
-async fn goxlr_preflight() -> Result<String, String> {
-    // Because Windows also supports unix sockets, we need to maintain legacy behaviour..
-    let path = if cfg!(windows) {
-        NAMED_PIPE.to_ns_name::<GenericNamespaced>()
+fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().skip(1).any(|arg| arg == flag)
+}
+
+fn create_main_window(handle: &AppHandle) -> tauri::Result<WebviewWindow> {
+    let window =
+        WebviewWindowBuilder::new(handle, WINDOW_NAME, WebviewUrl::App("index.html".into()))
+            .title("GoXLR Utility")
+            .inner_size(1300., 910.)
+            .resizable(true)
+            .visible(false)
+            .initialization_script(UTILITY_SCRIPT)
+            .on_navigation(navigation::navigation_guard(handle.clone()))
+            .build()?;
+
+    #[cfg(target_os = "linux")]
+    fallback::watch_renderer(&window);
+    Ok(window)
+}
+
+/// Checks whether a URL belongs to the Utility (rather than our loading page, or elsewhere)
+fn is_utility_url(handle: &AppHandle, url: &Url) -> bool {
+    let state = handle.state::<MonitorState>();
+    let address = state.address.lock().unwrap();
+    if let Some(address) = address
+        .as_ref()
+        .and_then(|address| Url::parse(address).ok())
+    {
+        return address.origin() == url.origin();
+    }
+    false
+}
+
+/// Without a webview, requests to show the UI are sent to the browser instead, and if the webview
+/// has been released it needs to be recreated. Returns false if the windows can simply be shown.
+fn redirect_show(handle: &AppHandle) -> bool {
+    if fallback::is_active(handle) {
+        tokio::task::spawn(fallback::request_open(handle.clone()));
+        return true;
+    }
+    if memory::is_released(handle) {
+        tokio::task::spawn(memory::restore(handle.clone()));
+        return true;
+    }
+    false
+}
+
+fn show_window(window: &WebviewWindow) {
+    let _ = window.show();
+    let _ = window.unminimize();
+    let _ = window.set_focus();
+
+    #[cfg(target_os = "macos")]
+    set_dock_visible(true);
+}
+
+fn hide_window(window: &Window) {
+    let _ = window.hide();
+
+    // Only hide the dock icon once all our windows are hidden..
+    #[cfg(target_os = "macos")]
+    {
+        let windows = devices::get_ui_windows(window.app_handle());
+        if !windows.iter().any(|w| w.is_visible().unwrap_or(false)) {
+            set_dock_visible(false);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_dock_visible(visible: bool) {
+    let update = move |mtm: macos::MainThreadMarker| {
+        if visible {
+            macos::show_dock(mtm)
+        } else {
+            macos::hide_dock(mtm)
+        }
+    };
+
+    match macos::MainThreadMarker::new() {
+        Some(mtm) => update(mtm),
+        None => {
+            macos::Queue::main()
+                .exec_async(move || update(macos::MainThreadMarker::new().unwrap()));
+        }
+    };
+}
+
+fn window_state_flags() -> StateFlags {
+    // We manage the visibility ourselves, otherwise the window will be shown if it was
+    // visible when the app last exited.
+    StateFlags::all() & !StateFlags::VISIBLE
+}
+
+fn apply_close_behaviour(window: &Window, behaviour: CloseBehaviour) {
+    match behaviour {
+        CloseBehaviour::Hide | CloseBehaviour::Ask => hide_window(window),
+        CloseBehaviour::Minimize => {
+            let _ = window.minimize();
+        }
+        CloseBehaviour::Quit => {
+            let _ = window.app_handle().emit(QUIT_EVENT_NAME, None::<String>);
+        }
+    }
+}
+
+/// Asks the user what closing the window should do, saving their answer if they want it
+/// remembered. Returns None if they cancelled, in which case the window should stay open.
+async fn ask_close_behaviour(handle: &AppHandle) -> Option<CloseBehaviour> {
+    let title = String::from("GoXLR Utility UI");
+    let message = String::from(
+        "Keep the GoXLR Utility UI running in the background?\n\nQuitting will close the app \
+        until it's next opened from the GoXLR Utility.",
+    );
+
+    let choice = run_dialog(handle, move || show_close_option(title, message)).await;
+    let (keep_running, remember) = choice.flatten()?;
+    let behaviour = if keep_running {
+        CloseBehaviour::Hide
     } else {
-        SOCKET_PATH.to_fs_name::<GenericFilePath>()
+        CloseBehaviour::Quit
+    };
+
+    if remember {
+        let config = handle.state::<Mutex<Config>>();
+        let mut config = config.lock().unwrap();
+        config.close_behaviour = behaviour;
+        if let Err(e) = config.save() {
+            warn!("Unable to save settings: {}", e);
+        }
     }
-    .map_err(|e| format!("Unable to get path to socket: {}", e))?;
+    Some(behaviour)
+}
 
-    let connection = LocalSocketStream::connect(path).await;
+/// Checks the Utility is ready for us, then hands over to the monitor. This is also used when
+/// retrying from the error page.
+async fn run_startup(handle: AppHandle) {
+    match goxlr_preflight(&handle).await {
+        Ok(host) => goxlr_utility_monitor(handle, host).await,
+        Err(e) => {
+            error!("{}", e);
 
-    if connection.is_err() {
-        let message = "The GoXLR Utility must be running before launching this app.";
-        return Err(message.into());
+            // Without a window, a dialog is the only way to let the user know what happened.
+            if fallback::is_active(&handle) {
+                show_error("GoXLR Utility UI".into(), e.to_string());
+                let _ = handle.emit(STOP_EVENT_NAME, None::<String>);
+                return;
+            }
+            pages::show_error_page(&handle, &e.to_string(), e.hint());
+        }
     }
-    let mut socket: Socket<Value, Value> = Socket::new(connection.unwrap());
-    if socket.send(json!("GetStatus")).await.is_ok() {
-        if let Ok(Some(result)) = socket.try_read().await {
-            // Firstly, preform the general config check, and see if we need to run a command..
-            if let Some(command) = check_app_runtime(&result)? {
-                // We need to send a command to the GoXLR..
-                if socket.send(command).await.is_ok() {
-                    // We don't actually care about the result, just clear the response..
-                    let _ = socket.try_read().await;
-                }
+}
+
+/// The reasons the Utility might not be ready for us, each needs something different from the user
+#[derive(Debug)]
+enum PreflightError {
+    NotRunning,
+    Denied(String),
+    Declined,
+    Failed(String),
+}
+
+impl PreflightError {
+    fn hint(&self) -> &'static str {
+        match self {
+            Self::NotRunning => "Make sure the GoXLR Utility is running, then press Retry.",
+            Self::Denied(_) => "Make sure the GoXLR Utility is running as you, then press Retry.",
+            Self::Declined => "Press Retry if you'd like this app to control your GoXLR.",
+            Self::Failed(_) => "Make sure the GoXLR Utility is up to date, then press Retry.",
+        }
+    }
+}
+
+impl std::fmt::Display for PreflightError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotRunning => {
+                write!(
+                    f,
+                    "The GoXLR Utility must be running before launching this app."
+                )
             }
+            Self::Denied(message) | Self::Failed(message) => write!(f, "{}", message),
+            Self::Declined => write!(f, "Unable to obtain User Consent"),
+        }
+    }
+}
 
-            // Now, grab the HTTP Address / Port for the utility..
-            return get_goxlr_host(&result);
+async fn goxlr_preflight(handle: &AppHandle) -> Result<String, PreflightError> {
+    startup::phase(handle, "Connecting to the GoXLR Utility..");
+    let connection = ipc::connect().await;
+    if let Err(e) = &connection {
+        if e.kind() == ErrorKind::PermissionDenied {
+            return Err(PreflightError::Denied(e.to_string()));
         }
+        return Err(PreflightError::NotRunning);
     }
-    Err("Unable to locate the Utility's URL".into())
+    let mut socket: Socket<Value, Value> = Socket::new(connection.unwrap());
+    startup::phase(handle, "Checking the GoXLR Utility's configuration..");
+    if let Ok(result) = ipc::request_status(&mut socket).await {
+        // Firstly, preform the general config check, and see if we need to run a command..
+        if let Some(command) = check_app_runtime(handle, &result).await? {
+            // We need to send a command to the GoXLR..
+            startup::phase(handle, "Activating..");
+            if socket.send(command).await.is_ok() {
+                // We don't actually care about the result, just clear the response..
+                let _ = socket.try_read().await;
+            }
+        }
+
+        // Now, grab the HTTP Address / Port for the utility..
+        return get_goxlr_host(&result).map_err(PreflightError::Failed);
+    }
+    Err(PreflightError::Failed(
+        "Unable to locate the Utility's URL".into(),
+    ))
 }
 
-fn check_app_runtime(status: &Value) -> Result<Option<Value>, String> {
+async fn check_app_runtime(
+    handle: &AppHandle,
+    status: &Value,
+) -> Result<Option<Value>, PreflightError> {
     if let Some(status) = status.get("Status") {
         if let Some(config) = status.get("config") {
             if let Some(activation) = config.get("activation") {
@@ -208,17 +635,18 @@ fn check_app_runtime(status: &Value) -> Result<Option<Value>, String> {
                         }
                         found
                     };
-                    println!("{:#?}", path);
+                    debug!("Activation Path: {:?}", path);
 
                     return if path.is_none() || PathBuf::from(path.unwrap()) != exe {
+                        startup::phase(handle, "Waiting for confirmation..");
                         let title = String::from("GoXLR Utility UI");
                         let message = String::from("Use this app to control your GoXLR?");
-                        if show_option(title, message).is_ok() {
+                        if ask_user(handle, title, message).await {
                             let command = get_activator_command(Some(exe));
                             let json = serde_json::from_str::<Value>(&command).unwrap();
                             Ok(Some(json))
                         } else {
-                            Err("Unable to obtain User Consent".into())
+                            Err(PreflightError::Declined)
                         }
                     } else {
                         // We're already setup, nothing more to do here..
@@ -231,7 +659,44 @@ fn check_app_runtime(status: &Value) -> Result<Option<Value>, String> {
             }
         }
     }
-    Err("Unable to locate Activation Path".into())
+    Err(PreflightError::Failed(
+        "Unable to locate Activation Path".into(),
+    ))
+}
+
+/// Runs a dialog without blocking the event loop (on macOS dialogs need to be on the main thread,
+/// so it's sent there instead). Returns None if the dialog was unable to run.
+async fn run_dialog<T, F>(handle: &AppHandle, dialog: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    #[cfg(target_os = "macos")]
+    {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let _ = handle.run_on_main_thread(move || {
+            let _ = tx.send(dialog());
+        });
+        rx.await.ok()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = handle;
+        tokio::task::spawn_blocking(dialog).await.ok()
+    }
+}
+
+/// Asks the user a question without blocking the event loop
+async fn ask_user(handle: &AppHandle, title: String, message: String) -> bool {
+    run_dialog(handle, move || show_option(title, message).is_ok())
+        .await
+        .unwrap_or(false)
+}
+
+/// Shows the user an error without blocking the event loop
+async fn tell_user(handle: &AppHandle, title: String, message: String) {
+    run_dialog(handle, move || show_error(title, message)).await;
 }
 
 fn get_goxlr_host(status: &Value) -> Result<String, String> {
@@ -242,10 +707,12 @@ fn get_goxlr_host(status: &Value) -> Result<String, String> {
                     if let Some(address) = address.as_str() {
                         if let Some(port) = http_settings.get("port") {
                             if let Some(port) = port.as_u64() {
-                                let address = if address != "localhost" && address == "0.0.0.0" {
-                                    "localhost"
-                                } else {
-                                    address
+                                let address = match address {
+                                    // Bound to all interfaces, so we can just use localhost..
+                                    "0.0.0.0" | "::" => String::from("localhost"),
+                                    // IPv6 Addresses need to be wrapped to be used in a URL
+                                    address if address.contains(':') => format!("[{}]", address),
+                                    address => String::from(address),
                                 };
                                 Ok(format!("{}:{}", address, port))
                             } else {
@@ -271,14 +738,82 @@ fn get_goxlr_host(status: &Value) -> Result<String, String> {
     }
 }
 
+/// Parses and validates the address sent with the ready event, optionally attaching a route to
+/// it, we only ever want to navigate to an http(s) address with a host and port.
+fn get_utility_url(payload: &str, route: Option<&str>) -> Result<Url, String> {
+    let address: String = serde_json::from_str(payload)
+        .map_err(|e| format!("Unable to parse address {}: {}", payload, e))?;
+    let base = Url::parse(&address).map_err(|e| format!("Invalid address {}: {}", address, e))?;
+
+    if base.scheme() != "http" && base.scheme() != "https" {
+        return Err(format!("Unsupported scheme in {}", base));
+    }
+    if base.host_str().unwrap_or_default().is_empty() {
+        return Err(format!("Missing host in {}", base));
+    }
+    if !is_local_host(base.host_str().unwrap_or_default()) {
+        return Err(format!("Non-local host in {}", base));
+    }
+    if base.port().is_none() {
+        return Err(format!("Missing port in {}", base));
+    }
+
+    if let Some(route) = route {
+        // Make sure the route can't take us somewhere else, if it's not valid, ignore it.
+        if let Ok(url) = base.join(route) {
+            if route.starts_with('/') && url.origin() == base.origin() {
+                return Ok(url);
+            }
+        }
+        warn!("Ignoring invalid route: {}", route);
+    }
+    Ok(base)
+}
+
+/// The Utility runs on this machine, so should only ever be bound to localhost, or to one of
+/// this machine's own addresses.
+fn is_local_host(host: &str) -> bool {
+    if host == "localhost" {
+        return true;
+    }
+    match host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse::<IpAddr>()
+    {
+        Ok(ip) if ip.is_loopback() => true,
+        Ok(ip) if ip.is_unspecified() => false,
+        // Binding only succeeds for addresses assigned to one of this machine's interfaces.
+        Ok(ip) => UdpSocket::bind((ip, 0)).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Sets the device a URL is for, replacing any serial which came with the restored route
+fn set_serial(url: &mut Url, serial: Option<&str>) {
+    let pairs: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "serial")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    if pairs.is_empty() && serial.is_none() {
+        url.set_query(None);
+        return;
+    }
+    let mut query = url.query_pairs_mut();
+    query.clear().extend_pairs(pairs);
+    if let Some(serial) = serial {
+        query.append_pair("serial", serial);
+    }
+}
+
 async fn supports_activation(socket: &mut Socket<Value, Value>) -> bool {
-    if socket.send(json!("GetStatus")).await.is_ok() {
-        if let Ok(Some(result)) = socket.try_read().await {
-            if let Some(status) = result.get("Status") {
-                if let Some(config) = status.get("config") {
-                    if config.get("activation").is_some() {
-                        return true;
-                    }
+    if let Ok(result) = ipc::request_status(socket).await {
+        if let Some(status) = result.get("Status") {
+            if let Some(config) = status.get("config") {
+                if config.get("activation").is_some() {
+                    return true;
                 }
             }
         }
@@ -286,61 +821,203 @@ async fn supports_activation(socket: &mut Socket<Value, Value>) -> bool {
     false
 }
 
-async fn goxlr_utility_monitor(handle: AppHandle, host: String) {
-    println!("Spawning the Monitor.. {}", host);
+async fn get_utility_host() -> Result<String, String> {
+    let status = ipc::get_status().await.map_err(|e| e.to_string())?;
+    get_goxlr_host(&status)
+}
+
+async fn goxlr_utility_monitor(handle: AppHandle, mut host: String) {
+    info!("Spawning the Monitor.. {}", host);
 
     // Grab and Parse the URL..
     let ws_address = format!("ws://{}/api/websocket", host);
-    let http_address = format!("http://{}/", host);
+    let mut http_address = format!("http://{}/", host);
 
     // Attempt to connect to the websocket..
-    let result = connect(ws_address.as_str());
-    if result.is_err() {
-        // Hide the UI itself before showing the error..
-        let _ = handle.emit(HIDE_EVENT_NAME, None::<String>);
-
-        // We only support windows for these currently..
-        show_error(
-            "Unable to Launch UI".to_string(),
-            "Unable to connect to the GoXLR Utility".to_string(),
-        );
+    info!("Connecting to websocket at {}", ws_address);
+    startup::phase(&handle, "Connecting to the GoXLR Utility's interface..");
+    let result = connect_websocket(ws_address).await;
+    if let Err(e) = &result {
+        error!("Unable to connect to websocket: {}", e);
 
-        // Now drop and quit.
-        let _ = handle.emit(STOP_EVENT_NAME, None::<String>);
+        // The window exists at this point, so let it explain what went wrong.
+        pages::show_error_page(
+            &handle,
+            "Unable to connect to the GoXLR Utility",
+            "Make sure the GoXLR Utility is running, and that its web interface is enabled.",
+        );
         return;
     }
 
     // Got a good connection, grab the socket..
-    let (mut socket, _) = result.unwrap();
-    // Trigger the event that lets the window know we're ready..
-    let _ = handle.emit(READY_EVENT_NAME, &http_address);
+    let mut socket = result.unwrap();
+    info!("Websocket connected");
+    request_status(&mut socket);
 
-    // Anything that's not a valid message, or is a 'Close' message breaks the loop.
-    while let Ok(message) = socket.read() {
-        if let Message::Close(..) = message {
-            // Break the loop so we can shutdown the app
+    // Once the UI is being served, trigger the event that lets the window know we're ready..
+    let state = handle.state::<MonitorState>();
+    state.connected.store(true, Ordering::Relaxed);
+    set_utility_address(&handle, &http_address);
+    startup::phase(&handle, "Loading the GoXLR Utility's interface..");
+    readiness::load_utility(&handle, &http_address).await;
+
+    loop {
+        // Reads on the websocket block, so they're kept off the async runtime.
+        let monitor = handle.clone();
+        let _ = tokio::task::spawn_blocking(move || monitor_socket(&monitor, socket)).await;
+        if state.shutdown.load(Ordering::Relaxed) {
             break;
         }
+
+        // We've lost the connection, the Utility may simply be restarting, so give it a chance
+        // to come back before we give up.
+        warn!("Websocket closed, attempting to reconnect..");
+        state.connected.store(false, Ordering::Relaxed);
+        handle.state::<StateStore>().clear();
+        title::set_reconnecting(&handle);
+        notifications::notify(&handle, notifications::Event::UtilityLost);
+
+        match reconnect(&host).await {
+            Some((new_host, new_socket)) => {
+                if new_host != host {
+                    info!("Utility address changed to {}", new_host);
+                    http_address = format!("http://{}/", new_host);
+                    host = new_host;
+                }
+
+                info!("Websocket reconnected");
+                socket = new_socket;
+                request_status(&mut socket);
+                state.connected.store(true, Ordering::Relaxed);
+                set_utility_address(&handle, &http_address);
+                title::set_connected(&handle);
+                notifications::notify(&handle, notifications::Event::UtilityRestored);
+
+                // Reload the UI, so it picks up the new connection..
+                readiness::load_utility(&handle, &http_address).await;
+            }
+            None => {
+                warn!("Unable to reconnect to the Utility, shutting down");
+                break;
+            }
+        }
     }
+
     // Loop Ended, this happens when socket is closed.
     let _ = handle.emit(STOP_EVENT_NAME, None::<String>);
 }
 
-// Installs this app into the util..
-async fn manage(install: bool) -> Result<(), String> {
-    println!("Checking if Utility is Running..");
-    let path = if cfg!(windows) {
-        NAMED_PIPE.to_ns_name::<GenericNamespaced>()
-    } else {
-        SOCKET_PATH.to_fs_name::<GenericFilePath>()
+/// Connects to the Utility's websocket, this blocks so is kept off the async runtime
+async fn connect_websocket(
+    address: String,
+) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, String> {
+    let result = tokio::task::spawn_blocking(move || connect(address.as_str()))
+        .await
+        .map_err(|e| e.to_string())?;
+    let (socket, _) = result.map_err(|e| e.to_string())?;
+
+    // Reads block, so set a timeout to allow us to periodically check if we're shutting down.
+    if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+        let _ = stream.set_read_timeout(Some(Duration::from_millis(250)));
+    }
+    Ok(socket)
+}
+
+/// Asks the Utility for its full status, further changes will be sent to us as patches
+fn request_status(socket: &mut WebSocket<MaybeTlsStream<TcpStream>>) {
+    let request = json!({ "id": 0, "data": "GetStatus" });
+    if let Err(e) = socket.send(Message::text(request.to_string())) {
+        warn!("Unable to request status: {}", e);
+    }
+}
+
+fn monitor_socket(handle: &AppHandle, mut socket: WebSocket<MaybeTlsStream<TcpStream>>) {
+    let state = handle.state::<MonitorState>();
+    let socket = &mut socket;
+
+    // Anything that's not a valid message, or is a 'Close' message breaks the loop.
+    loop {
+        if state.refresh.swap(false, Ordering::Relaxed) {
+            request_status(socket);
+        }
+
+        match socket.read() {
+            Ok(Message::Close(frame)) => {
+                info!("Websocket closed by the Utility: {:?}", frame);
+                break;
+            }
+            Ok(Message::Text(text)) => {
+                // If we've fallen out of sync with the Utility, start again from a fresh status.
+                let store = handle.state::<StateStore>();
+                if let Err(e) = store.handle_message(&text) {
+                    warn!("Unable to update state: {}, requesting status", e);
+                    request_status(socket);
+                }
+            }
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut =>
+            {
+                if state.shutdown.load(Ordering::Relaxed) {
+                    // We've been asked to quit, let the Utility know we're going away..
+                    info!("Shutdown requested, closing websocket");
+                    let _ = socket.close(None);
+                    while socket.read().is_ok() {}
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("Websocket error: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+async fn reconnect(host: &str) -> Option<(String, WebSocket<MaybeTlsStream<TcpStream>>)> {
+    let start = Instant::now();
+    while start.elapsed() < RECONNECT_TIMEOUT {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // The Utility may have come back on a different address, so check with it first..
+        let host = get_utility_host()
+            .await
+            .unwrap_or_else(|_| host.to_string());
+        let ws_address = format!("ws://{}/api/websocket", host);
+        debug!("Attempting to reconnect to {}", ws_address);
+        if let Ok(socket) = connect_websocket(ws_address).await {
+            return Some((host, socket));
+        }
     }
-    .map_err(|e| format!("Unable to get path to socket: {}", e))?;
+    None
+}
 
-    let connection = LocalSocketStream::connect(path).await;
+/// Stores the Utility's address, and permits it access to the IPC capabilities it needs
+fn set_utility_address(handle: &AppHandle, http_address: &str) {
+    let state = handle.state::<MonitorState>();
+    state
+        .address
+        .lock()
+        .unwrap()
+        .replace(http_address.to_string());
 
-    if connection.is_err() {
-        println!("Utility Not Running, changing config directly..");
-        println!("Locating Settings File..");
+    if let Ok(url) = Url::parse(http_address) {
+        if let Err(e) = navigation::grant_capabilities(handle, &url) {
+            error!("Unable to grant capabilities to {}: {}", url, e);
+        }
+    }
+}
+
+// Installs this app into the util..
+async fn manage(install: bool) -> Result<(), String> {
+    info!("Checking if Utility is Running..");
+    let connection = ipc::connect().await;
+    if let Err(e) = &connection {
+        if e.kind() == ErrorKind::PermissionDenied {
+            return Err(e.to_string());
+        }
+        info!("Utility Not Running, changing config directly..");
+        info!("Locating Settings File..");
         let path = get_settings_file();
         let json = if !&path.exists() {
             if !install {
@@ -354,7 +1031,7 @@ async fn manage(install: bool) -> Result<(), String> {
         };
         write_settings(&path, json, install);
     } else {
-        println!("Utility Running, attempting via IPC");
+        info!("Utility Running, attempting via IPC");
         let method = if install { "Install" } else { "Remove" };
 
         let mut socket: Socket<Value, Value> = Socket::new(connection.unwrap());
@@ -368,7 +1045,7 @@ async fn manage(install: bool) -> Result<(), String> {
             let command = get_activator_command(path);
 
             // Attempt to Register ourselves as the UI App..
-            println!("Executing: {}", command);
+            info!("Executing: {}", command);
 
             let json = serde_json::from_str::<Value>(&command).unwrap();
             let _ = socket.send(json).await;
@@ -400,7 +1077,7 @@ fn get_platform_path(exe: PathBuf) -> String {
 
 fn get_current_path() -> PathBuf {
     if let Ok(app_image) = env::var("APPIMAGE") {
-        println!("Using AppImage at {}", &app_image);
+        debug!("Using AppImage at {}", &app_image);
         PathBuf::from(app_image)
     } else {
         env::current_exe().unwrap()
@@ -408,7 +1085,7 @@ fn get_current_path() -> PathBuf {
 }
 
 fn create_settings_path(path: &Path) {
-    println!("Creating path if needed..");
+    info!("Creating path if needed..");
     if let Some(parent) = path.parent() {
         if let Err(e) = create_dir_all(parent) {
             if e.kind() != ErrorKind::AlreadyExists {
@@ -419,7 +1096,7 @@ fn create_settings_path(path: &Path) {
 }
 
 fn load_settings(path: &PathBuf) -> Value {
-    println!("Loading Existing Settings..");
+    info!("Loading Existing Settings..");
     let path_str = String::from(path.to_string_lossy());
     match File::open(path) {
         Ok(reader) => serde_json::from_reader(reader)
@@ -468,7 +1145,7 @@ fn show_error(title: String, message: String) {
         .arg(message.clone())
         .output()
     {
-        println!("Error Running kdialog: {}, falling back to zenity..", e);
+        warn!("Error Running kdialog: {}, falling back to zenity..", e);
         let _ = Command::new("zenity")
             .arg("--title")
             .arg(title)
@@ -514,6 +1191,62 @@ fn show_option(title: String, message: String) -> Result<(), ()> {
     }
 }
 
+/// Asks whether to keep running or quit, returns the answer and whether to remember it
+#[cfg(target_os = "linux")]
+fn show_close_option(title: String, message: String) -> Option<(bool, bool)> {
+    use std::process::Command;
+    let options = [
+        ("hide", "Keep running in the background"),
+        (
+            "hide-remember",
+            "Keep running in the background, and don't ask again",
+        ),
+        ("quit", "Quit"),
+        ("quit-remember", "Quit, and don't ask again"),
+    ];
+
+    let mut kdialog = Command::new("kdialog");
+    kdialog
+        .arg("--title")
+        .arg(&title)
+        .arg("--radiolist")
+        .arg(&message);
+    for (index, (tag, label)) in options.iter().enumerate() {
+        kdialog.args([tag, label, if index == 0 { "on" } else { "off" }]);
+    }
+
+    // Both print the chosen tag, and fail if the dialog is cancelled.
+    let output = match kdialog.output() {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Error Running kdialog: {}, falling back to zenity..", e);
+            let mut zenity = Command::new("zenity");
+            zenity
+                .arg("--title")
+                .arg(&title)
+                .args(["--list", "--radiolist", "--hide-header", "--text"])
+                .arg(&message)
+                .args(["--column", "", "--column", "", "--column", ""])
+                .args(["--hide-column=2", "--print-column=2"]);
+            for (index, (tag, label)) in options.iter().enumerate() {
+                zenity.args([if index == 0 { "TRUE" } else { "FALSE" }, tag, label]);
+            }
+            zenity.output().ok()?
+        }
+    };
+    if !output.status.success() {
+        return None;
+    }
+
+    match String::from_utf8_lossy(&output.stdout).trim() {
+        "hide" => Some((true, false)),
+        "hide-remember" => Some((true, true)),
+        "quit" => Some((false, false)),
+        "quit-remember" => Some((false, true)),
+        _ => None,
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn show_error(title: String, message: String) {
     use windows::core::HSTRING;
@@ -527,6 +1260,56 @@ fn show_error(title: String, message: String) {
     }
 }
 
+#[cfg(target_os = "windows")]
+fn show_close_option(title: String, message: String) -> Option<(bool, bool)> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::Foundation::BOOL;
+    use windows::Win32::UI::Controls::{
+        TaskDialogIndirect, TASKDIALOGCONFIG, TASKDIALOG_BUTTON, TDCBF_CANCEL_BUTTON,
+    };
+
+    const KEEP_RUNNING: i32 = 100;
+    const QUIT: i32 = 101;
+
+    let title = HSTRING::from(title);
+    let message = HSTRING::from(message);
+    let remember = HSTRING::from("Don't ask again");
+    let keep_running = HSTRING::from("Keep Running");
+    let quit = HSTRING::from("Quit");
+
+    let buttons = [
+        TASKDIALOG_BUTTON {
+            nButtonID: KEEP_RUNNING,
+            pszButtonText: PCWSTR(keep_running.as_ptr()),
+        },
+        TASKDIALOG_BUTTON {
+            nButtonID: QUIT,
+            pszButtonText: PCWSTR(quit.as_ptr()),
+        },
+    ];
+
+    let config = TASKDIALOGCONFIG {
+        cbSize: size_of::<TASKDIALOGCONFIG>() as u32,
+        pszWindowTitle: PCWSTR(title.as_ptr()),
+        pszContent: PCWSTR(message.as_ptr()),
+        pszVerificationText: PCWSTR(remember.as_ptr()),
+        dwCommonButtons: TDCBF_CANCEL_BUTTON,
+        cButtons: buttons.len() as u32,
+        pButtons: buttons.as_ptr(),
+        nDefaultButton: KEEP_RUNNING,
+        ..Default::default()
+    };
+
+    let mut button = 0;
+    let mut checked = BOOL(0);
+    unsafe { TaskDialogIndirect(&config, Some(&mut button), None, Some(&mut checked)) }.ok()?;
+    match button {
+        KEEP_RUNNING => Some((true, checked.as_bool())),
+        QUIT => Some((false, checked.as_bool())),
+        _ => None,
+    }
+}
+
 #[cfg(target_os = "windows")]
 fn show_option(title: String, message: String) -> Result<(), ()> {
     use windows::core::HSTRING;
@@ -561,8 +1344,157 @@ fn show_error(title: String, message: String) {
     };
 }
 
+#[cfg(target_os = "macos")]
+fn show_close_option(title: String, message: String) -> Option<(bool, bool)> {
+    let mtm = macos::MainThreadMarker::new().unwrap();
+    macos::show_close_question(mtm, title, message)
+}
+
 #[cfg(target_os = "macos")]
 fn show_option(title: String, message: String) -> Result<(), ()> {
     let mtm = macos::MainThreadMarker::new().unwrap();
     macos::show_question(mtm, title, message)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(address: &str) -> String {
+        serde_json::to_string(address).unwrap()
+    }
+
+    fn status(bind_address: &str) -> Value {
+        json!({"Status": {"config": {"http_settings": {"bind_address": bind_address, "port": 14564}}}})
+    }
+
+    #[test]
+    fn host_from_bind_address() {
+        assert_eq!(
+            get_goxlr_host(&status("127.0.0.1")).unwrap(),
+            "127.0.0.1:14564"
+        );
+        assert_eq!(
+            get_goxlr_host(&status("0.0.0.0")).unwrap(),
+            "localhost:14564"
+        );
+    }
+
+    #[test]
+    fn host_from_ipv6_bind_address() {
+        assert_eq!(get_goxlr_host(&status("::1")).unwrap(), "[::1]:14564");
+        assert_eq!(get_goxlr_host(&status("::")).unwrap(), "localhost:14564");
+
+        // The result should always be usable in a URL
+        let address = format!("http://{}/", get_goxlr_host(&status("::1")).unwrap());
+        assert!(get_utility_url(&payload(&address), None).is_ok());
+    }
+
+    #[test]
+    fn host_requires_http_settings() {
+        assert!(get_goxlr_host(&json!({"Status": {"config": {}}})).is_err());
+        assert!(get_goxlr_host(&json!({"Error": "Nope"})).is_err());
+    }
+
+    #[test]
+    fn preflight_hints_match_the_error() {
+        let running = PreflightError::NotRunning.hint();
+        assert!(running.contains("is running"));
+        assert_ne!(PreflightError::Declined.hint(), running);
+        assert_ne!(PreflightError::Denied(String::new()).hint(), running);
+        assert_ne!(PreflightError::Failed(String::new()).hint(), running);
+    }
+
+    #[test]
+    fn accepts_local_addresses() {
+        for address in [
+            "http://localhost:14564/",
+            "http://127.0.0.1:14564/",
+            "https://127.0.0.1:14564/",
+            "http://[::1]:14564/",
+        ] {
+            assert!(
+                get_utility_url(&payload(address), None).is_ok(),
+                "{}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_bad_scheme() {
+        for address in [
+            "ftp://localhost:14564/",
+            "javascript:alert(1)",
+            "file:///etc/passwd",
+        ] {
+            assert!(
+                get_utility_url(&payload(address), None).is_err(),
+                "{}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_non_local_host() {
+        for address in [
+            "http://evil.host:14564/",
+            "http://8.8.8.8:14564/",
+            "http://[2001:db8::1]:14564/",
+            // Other machines on the local network
+            "http://10.254.254.254:14564/",
+            "http://[fd00::dead:beef]:14564/",
+            "http://0.0.0.0:14564/",
+        ] {
+            assert!(
+                get_utility_url(&payload(address), None).is_err(),
+                "{}",
+                address
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_missing_port() {
+        assert!(get_utility_url(&payload("http://localhost/"), None).is_err());
+        assert!(get_utility_url(&payload("https://127.0.0.1/"), None).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_payload() {
+        assert!(get_utility_url("window.location = 'http://evil.host'", None).is_err());
+        assert!(get_utility_url(&payload("not a url"), None).is_err());
+    }
+
+    #[test]
+    fn applies_valid_routes() {
+        let url = get_utility_url(&payload("http://localhost:14564/"), Some("/mic#top")).unwrap();
+        assert_eq!(url.as_str(), "http://localhost:14564/mic#top");
+    }
+
+    #[test]
+    fn ignores_routes_leaving_the_utility() {
+        for route in ["//evil.host/", "http://evil.host/", "mic", "/\\evil.host/"] {
+            let url = get_utility_url(&payload("http://localhost:14564/"), Some(route)).unwrap();
+            assert_eq!(url.as_str(), "http://localhost:14564/", "{}", route);
+        }
+    }
+
+    #[test]
+    fn replaces_serial_from_route() {
+        let mut url = Url::parse("http://localhost:14564/?serial=OLD&tab=mic#top").unwrap();
+        set_serial(&mut url, Some("NEW"));
+        assert_eq!(
+            url.as_str(),
+            "http://localhost:14564/?tab=mic&serial=NEW#top"
+        );
+
+        set_serial(&mut url, None);
+        assert_eq!(url.as_str(), "http://localhost:14564/?tab=mic#top");
+
+        let mut url = Url::parse("http://localhost:14564/?serial=OLD").unwrap();
+        set_serial(&mut url, None);
+        assert_eq!(url.as_str(), "http://localhost:14564/");
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::Config;
+use crate::devices::{self, DeviceState};
+use crate::state::StateStore;
+use crate::{create_main_window, fallback, readiness, show_window, MonitorState, WINDOW_NAME};
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+static CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Tracks the webviews being recreated after they've been released
+#[derive(Default)]
+pub struct MemoryState {
+    /// Set while the window is being recreated, so repeated requests to show it don't race
+    restoring: AtomicBool,
+}
+
+impl MemoryState {
+    /// Returns false if the window is already being restored
+    fn start_restoring(&self) -> bool {
+        !self.restoring.swap(true, Ordering::Relaxed)
+    }
+
+    fn finish_restoring(&self) {
+        self.restoring.store(false, Ordering::Relaxed);
+    }
+}
+
+/// When enabled, destroys the webviews once they've been hidden for a while. The monitor and tray
+/// keep running, and the window is recreated the next time it's needed.
+pub async fn run(handle: AppHandle) {
+    let minutes = handle
+        .state::<Mutex<Config>>()
+        .lock()
+        .unwrap()
+        .low_memory_minutes;
+    if minutes == 0 {
+        return;
+    }
+    let limit = Duration::from_secs(minutes * 60);
+    info!(
+        "Low memory mode enabled, releasing the UI after {} minute(s) hidden",
+        minutes
+    );
+
+    let mut hidden_since: Option<Instant> = None;
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        // If the main window is already gone, there's nothing to release.
+        if handle.get_webview_window(WINDOW_NAME).is_none() {
+            hidden_since = None;
+            continue;
+        }
+
+        let windows = devices::get_ui_windows(&handle);
+        if windows.iter().any(|w| w.is_visible().unwrap_or(false)) {
+            hidden_since = None;
+            continue;
+        }
+
+        let since = *hidden_since.get_or_insert_with(Instant::now);
+        if since.elapsed() >= limit {
+            info!("UI hidden for {} minute(s), releasing the webview", minutes);
+            for window in windows {
+                let _ = window.destroy();
+            }
+            hidden_since = None;
+        }
+    }
+}
+
+/// Whether the window has been released, and needs recreating before it can be shown
+pub fn is_released(handle: &AppHandle) -> bool {
+    !fallback::is_active(handle) && handle.get_webview_window(WINDOW_NAME).is_none()
+}
+
+/// Recreates the main window on the loading page, then heads back to the Utility. Windows can't
+/// be built from a synchronous event handler (it deadlocks on Windows), so this needs spawning.
+pub async fn restore(handle: AppHandle) {
+    let state = handle.state::<MemoryState>();
+    if !state.start_restoring() {
+        return;
+    }
+    info!("Recreating the released webview");
+    let window = create_main_window(&handle);
+    state.finish_restoring();
+
+    let window = match window {
+        Ok(window) => window,
+        Err(e) => {
+            let reason = format!("Unable to recreate the main window: {}", e);
+            fallback::enable(&handle, &reason);
+            fallback::request_open(handle).await;
+            return;
+        }
+    };
+
+    let zoom = handle.state::<Mutex<Config>>().lock().unwrap().zoom;
+    let _ = window.set_zoom(zoom);
+    show_window(&window);
+
+    // Forget the known devices, and have their windows recreated.
+    handle
+        .state::<DeviceState>()
+        .devices
+        .lock()
+        .unwrap()
+        .clear();
+    handle.state::<StateStore>().refresh();
+
+    // If we're not connected, the monitor will load the UI once the Utility is back.
+    let monitor = handle.state::<MonitorState>();
+    if !monitor.connected.load(Ordering::Relaxed) {
+        info!("Utility not connected, waiting for the monitor to reconnect");
+        return;
+    }
+    let address = monitor.address.lock().unwrap().clone();
+    if let Some(address) = address {
+        readiness::load_utility(&handle, &address).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_restores_once_at_a_time() {
+        let state = MemoryState::default();
+        assert!(state.start_restoring());
+        assert!(!state.start_restoring());
+        state.finish_restoring();
+        assert!(state.start_restoring());
+    }
+}
@@ -0,0 +1,70 @@
+use crate::devices::DEVICE_WINDOW_PREFIX;
+use crate::{is_utility_url, WINDOW_NAME};
+use log::{info, warn};
+use std::sync::Mutex;
+use tauri::ipc::CapabilityBuilder;
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_opener::OpenerExt;
+
+/// Origins which have already been granted capabilities
+#[derive(Default)]
+pub struct CapabilityState {
+    granted: Mutex<Vec<String>>,
+}
+
+/// Permits the Utility's UI to open links in the system browser. This is only granted to the
+/// exact origin the Utility is running on, rather than to anything on localhost.
+pub fn grant_capabilities(handle: &AppHandle, url: &Url) -> tauri::Result<()> {
+    let origin = url.origin().ascii_serialization();
+
+    let state = handle.state::<CapabilityState>();
+    let mut granted = state.granted.lock().unwrap();
+    if granted.contains(&origin) {
+        return Ok(());
+    }
+
+    // Capabilities can't be removed once added, but if the address changes, the previous origin
+    // will be refused by the navigation guard anyway.
+    let capability = CapabilityBuilder::new(format!("utility-{}", granted.len()))
+        .local(false)
+        .remote(format!("{}/*", origin))
+        .window(WINDOW_NAME)
+        .window(format!("{}*", DEVICE_WINDOW_PREFIX))
+        .permission("opener:default")
+        .permission("opener:allow-open-url");
+
+    handle.add_capability(capability)?;
+    granted.push(origin);
+    Ok(())
+}
+
+/// Builds the navigation handler for our windows, only the Utility and our own bundled pages are
+/// permitted to load in-window, web links are sent to the system's browser, and anything else is
+/// simply blocked.
+pub fn navigation_guard(handle: AppHandle) -> impl Fn(&Url) -> bool + Send + 'static {
+    move |url| {
+        if is_local_url(url) || is_utility_url(&handle, url) {
+            return true;
+        }
+
+        if matches!(url.scheme(), "http" | "https" | "mailto") {
+            info!("Opening {} in the system browser", url);
+            if let Err(e) = handle.opener().open_url(url.as_str(), None::<&str>) {
+                warn!("Unable to open {}: {}", url, e);
+            }
+        } else {
+            warn!("Blocked navigation to {}", url);
+        }
+        false
+    }
+}
+
+/// Checks whether a URL is one of our bundled pages
+fn is_local_url(url: &Url) -> bool {
+    // Windows serves these over http(s)://tauri.localhost, everywhere else uses tauri://localhost
+    match url.scheme() {
+        "tauri" => url.host_str() == Some("localhost"),
+        "http" | "https" => url.host_str() == Some("tauri.localhost"),
+        _ => false,
+    }
+}
@@ -0,0 +1,253 @@
+use crate::config::Config;
+use crate::devices::Device;
+use crate::state::{Change, StateStore};
+use log::{debug, warn};
+#[cfg(not(windows))]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+#[cfg(not(windows))]
+use std::sync::Arc;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::sync::broadcast::error::RecvError;
+
+#[cfg(target_os = "linux")]
+static APP_NAME: &str = "GoXLR Utility";
+
+/// How long a notification stays on screen on Linux, in milliseconds
+#[cfg(target_os = "linux")]
+static NOTIFICATION_TIMEOUT: u32 = 10000;
+
+/// How many notifications can be waiting on a click at once
+#[cfg(not(windows))]
+static WAITERS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    DeviceConnected(Device),
+    DeviceDisconnected(Device),
+    UtilityLost,
+    UtilityRestored,
+}
+
+/// A notification waiting to be shown
+struct Notice {
+    summary: String,
+    body: String,
+    serial: Option<String>,
+}
+
+/// Hands notifications over to the thread which shows them
+#[derive(Default)]
+pub struct NotificationState {
+    sender: Mutex<Option<Sender<Notice>>>,
+}
+
+/// Follows the Utility's state, and raises notifications as devices come and go
+pub async fn run(handle: AppHandle) {
+    // Showing (and waiting on) a notification can block, so it's done away from the runtime.
+    let (sender, receiver) = mpsc::channel();
+    handle
+        .state::<NotificationState>()
+        .sender
+        .lock()
+        .unwrap()
+        .replace(sender);
+    let worker = handle.clone();
+    std::thread::spawn(move || show_notifications(worker, receiver));
+
+    let mut changes = handle.state::<StateStore>().subscribe();
+    loop {
+        let event = match changes.recv().await {
+            Ok(Change::DeviceAdded(device)) => Event::DeviceConnected(device),
+            Ok(Change::DeviceRemoved(device)) => Event::DeviceDisconnected(device),
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+        notify(&handle, event);
+    }
+}
+
+/// Raises a native notification for an event, if the user has it enabled
+pub fn notify(handle: &AppHandle, event: Event) {
+    let settings = handle
+        .state::<Mutex<Config>>()
+        .lock()
+        .unwrap()
+        .notifications
+        .clone();
+    let (enabled, summary, body, serial) = match &event {
+        Event::DeviceConnected(device) => (
+            settings.device_connected,
+            format!("{} Connected", device.model()),
+            format!("{} is ready to use", device.name()),
+            Some(device.serial.clone()),
+        ),
+        Event::DeviceDisconnected(device) => (
+            settings.device_disconnected,
+            format!("{} Disconnected", device.model()),
+            format!("{} has been disconnected", device.name()),
+            None,
+        ),
+        Event::UtilityLost => (
+            settings.utility_lost,
+            String::from("GoXLR Utility Disconnected"),
+            String::from("Lost connection to the GoXLR Utility, waiting for it to return.."),
+            None,
+        ),
+        Event::UtilityRestored => (
+            settings.utility_restored,
+            String::from("GoXLR Utility Reconnected"),
+            String::from("The connection to the GoXLR Utility has been restored"),
+            None,
+        ),
+    };
+
+    if !enabled {
+        return;
+    }
+    debug!("Sending notification for {:?}", event);
+
+    let notice = Notice {
+        summary,
+        body,
+        serial,
+    };
+    if let Some(sender) = handle
+        .state::<NotificationState>()
+        .sender
+        .lock()
+        .unwrap()
+        .as_ref()
+    {
+        let _ = sender.send(notice);
+    }
+}
+
+/// Shows notifications as they arrive. Waiting for a click holds on to a thread until the
+/// notification goes away, so that's left to a small, fixed set of waiters. If they're all busy,
+/// the notification is simply shown.
+#[cfg(not(windows))]
+fn show_notifications(handle: AppHandle, receiver: Receiver<Notice>) {
+    let (waiter_sender, waiter_receiver) = mpsc::channel::<Notice>();
+    let waiter_receiver = Arc::new(Mutex::new(waiter_receiver));
+    let idle = Arc::new(AtomicUsize::new(WAITERS));
+
+    for _ in 0..WAITERS {
+        let handle = handle.clone();
+        let receiver = waiter_receiver.clone();
+        let idle = idle.clone();
+        std::thread::spawn(move || loop {
+            let Ok(notice) = receiver.lock().unwrap().recv() else {
+                break;
+            };
+            show(&handle, notice, true);
+            idle.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    for notice in receiver {
+        let claimed = idle
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |idle| {
+                idle.checked_sub(1)
+            })
+            .is_ok();
+        if claimed {
+            let _ = waiter_sender.send(notice);
+        } else {
+            show(&handle, notice, false);
+        }
+    }
+}
+
+/// Clicks on a toast are delivered to a callback, so nothing needs to wait on them here.
+#[cfg(windows)]
+fn show_notifications(handle: AppHandle, receiver: Receiver<Notice>) {
+    for notice in receiver {
+        show(&handle, notice);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn show(handle: &AppHandle, notice: Notice, clickable: bool) {
+    use notify_rust::{Notification, Timeout};
+
+    // An explicit timeout asks the notification server to close the notification, rather than
+    // leaving it (and our wait on it) around until the user dismisses it.
+    let mut notification = Notification::new();
+    notification
+        .appname(APP_NAME)
+        .summary(&notice.summary)
+        .body(&notice.body)
+        .timeout(Timeout::Milliseconds(NOTIFICATION_TIMEOUT));
+
+    // Clicking a notification triggers its 'default' action.
+    if clickable {
+        notification.action("default", "Show");
+    }
+
+    match notification.show() {
+        Ok(notification) if clickable => notification.wait_for_action(|action| {
+            if action == "default" {
+                show_target(handle, notice.serial);
+            }
+        }),
+        Ok(_) => {}
+        Err(e) => warn!("Unable to show notification: {}", e),
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn show(handle: &AppHandle, notice: Notice, clickable: bool) {
+    use mac_notification_sys::{Notification, NotificationResponse};
+
+    let result = Notification::new()
+        .title(&notice.summary)
+        .message(&notice.body)
+        .wait_for_click(clickable)
+        .send();
+
+    match result {
+        Ok(NotificationResponse::Click) => show_target(handle, notice.serial),
+        Ok(_) => {}
+        Err(e) => warn!("Unable to show notification: {}", e),
+    }
+}
+
+#[cfg(windows)]
+fn show(handle: &AppHandle, notice: Notice) {
+    use tauri_winrt_notification::Toast;
+
+    // Toasts are attributed to the app registered under this ID by the installer's shortcut,
+    // development builds aren't installed, so borrow PowerShell's.
+    let app_id = if tauri::is_dev() {
+        Toast::POWERSHELL_APP_ID.to_string()
+    } else {
+        handle.config().identifier.clone()
+    };
+
+    let handle = handle.clone();
+    let serial = notice.serial;
+    let result = Toast::new(&app_id)
+        .title(&notice.summary)
+        .text1(&notice.body)
+        .on_activated(move |_| {
+            show_target(&handle, serial.clone());
+            Ok(())
+        })
+        .show();
+
+    if let Err(e) = result {
+        warn!("Unable to show notification: {}", e);
+    }
+}
+
+fn show_target(handle: &AppHandle, serial: Option<String>) {
+    use crate::{SHOW_DEVICE_EVENT_NAME, SHOW_EVENT_NAME};
+    use tauri::Emitter;
+
+    let _ = match serial {
+        Some(serial) => handle.emit(SHOW_DEVICE_EVENT_NAME, serial),
+        None => handle.emit(SHOW_EVENT_NAME, None::<String>),
+    };
+}
@@ -0,0 +1,164 @@
+use crate::config::{Config, OsdConfig};
+use crate::devices;
+use crate::overlay::create_floating_window;
+use crate::state::{Change, StateStore};
+use log::{info, warn};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, WebviewWindow};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+
+pub static OSD_WINDOW_NAME: &str = "volume-osd";
+
+/// This needs to match the transition in osd.html
+static FADE_DURATION: Duration = Duration::from_millis(400);
+
+static OSD_WIDTH: f64 = 320.;
+static OSD_HEIGHT: f64 = 72.;
+static OSD_BOTTOM_MARGIN: f64 = 120.;
+
+/// Briefly displays the channel and level whenever a volume changes
+pub async fn run(handle: AppHandle) {
+    let config = handle
+        .state::<Mutex<Config>>()
+        .lock()
+        .unwrap()
+        .volume_osd
+        .clone();
+    if !config.enabled {
+        return;
+    }
+    info!("Volume OSD enabled");
+
+    let delay = Duration::from_millis(config.delay_ms);
+    let mut changes = handle.state::<StateStore>().subscribe();
+    let mut hide_at: Option<Instant> = None;
+
+    loop {
+        // Wait for the next change, or until it's time to hide the OSD..
+        let change = match hide_at {
+            Some(deadline) => tokio::select! {
+                change = changes.recv() => change,
+                _ = tokio::time::sleep_until(deadline) => {
+                    hide_at = None;
+                    if let Some(window) = handle.get_webview_window(OSD_WINDOW_NAME) {
+                        let _ = window.hide();
+                    }
+                    continue;
+                }
+            },
+            None => changes.recv().await,
+        };
+
+        let (channel, volume) = match change {
+            Ok(Change::VolumeChanged {
+                channel, volume, ..
+            }) => (channel, volume),
+            Ok(Change::Disconnected) => {
+                hide_at = Some(Instant::now());
+                continue;
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        // The Utility's UI already shows this, so only show the OSD if it's not visible.
+        if config.only_when_hidden && is_ui_visible(&handle) {
+            continue;
+        }
+
+        if let Err(e) = show(&handle, &config, &channel, volume) {
+            warn!("Unable to show the volume OSD: {}", e);
+            continue;
+        }
+        hide_at = Some(Instant::now() + delay + FADE_DURATION);
+    }
+}
+
+fn is_ui_visible(handle: &AppHandle) -> bool {
+    devices::get_ui_windows(handle).iter().any(|window| {
+        window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+    })
+}
+
+fn show(handle: &AppHandle, config: &OsdConfig, channel: &str, volume: u8) -> tauri::Result<()> {
+    let percent = (volume as f64 / u8::MAX as f64 * 100.).round();
+    let name = serde_json::to_string(&get_display_name(channel)).unwrap_or_default();
+
+    // A new window won't have loaded osd.html yet, so the level is handed to it on creation.
+    let window = match handle.get_webview_window(OSD_WINDOW_NAME) {
+        Some(window) => {
+            window.eval(&format!(
+                "window.showVolume && window.showVolume({}, {});",
+                name, percent
+            ))?;
+            window
+        }
+        None => create_window(handle, config, &name, percent)?,
+    };
+    window.show()
+}
+
+fn create_window(
+    handle: &AppHandle,
+    config: &OsdConfig,
+    name: &str,
+    percent: f64,
+) -> tauri::Result<WebviewWindow> {
+    // The page reads these when it loads, so it knows what to show, and when to start fading out.
+    let script = format!(
+        "window.OSD_DELAY = {}; window.OSD_INITIAL = [{}, {}];",
+        config.delay_ms, name, percent
+    );
+
+    let size = LogicalSize::new(OSD_WIDTH, OSD_HEIGHT);
+    let window =
+        create_floating_window(handle, OSD_WINDOW_NAME, "osd.html", "Volume", size, &script)?;
+
+    // Sit towards the bottom of the primary display, like most OSDs.
+    if let Some(monitor) = window.primary_monitor()? {
+        let scale = monitor.scale_factor();
+        let size = monitor.size().to_logical::<f64>(scale);
+        let origin = monitor.position().to_logical::<f64>(scale);
+        let x = origin.x + (size.width - OSD_WIDTH) / 2.;
+        let y = origin.y + size.height - OSD_HEIGHT - OSD_BOTTOM_MARGIN;
+        window.set_position(LogicalPosition::new(x, y))?;
+    }
+    Ok(window)
+}
+
+/// Channels are named in PascalCase by the Utility, eg. 'LineIn' becomes 'Line In'
+fn get_display_name(channel: &str) -> String {
+    let mut name = String::new();
+    for (index, character) in channel.chars().enumerate() {
+        if index > 0 && character.is_uppercase() {
+            name.push(' ');
+        }
+        name.push(character);
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_single_words() {
+        assert_eq!(get_display_name("Mic"), "Mic");
+        assert_eq!(get_display_name("Headphones"), "Headphones");
+    }
+
+    #[test]
+    fn splits_pascal_case() {
+        assert_eq!(get_display_name("LineIn"), "Line In");
+        assert_eq!(get_display_name("MicMonitor"), "Mic Monitor");
+        assert_eq!(get_display_name("LineOut"), "Line Out");
+    }
+
+    #[test]
+    fn handles_empty_names() {
+        assert_eq!(get_display_name(""), "");
+    }
+}
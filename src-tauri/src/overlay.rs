@@ -0,0 +1,111 @@
+use crate::config::{Config, OverlayConfig};
+use crate::devices::get_devices;
+use crate::state::{get_mixer, is_mic_muted, Change, StateStore};
+use log::{info, warn};
+use serde_json::Value;
+use std::sync::Mutex;
+use tauri::{
+    AppHandle, LogicalPosition, LogicalSize, Manager, WebviewUrl, WebviewWindow,
+    WebviewWindowBuilder,
+};
+use tokio::sync::broadcast::error::RecvError;
+
+pub static OVERLAY_WINDOW_NAME: &str = "mic-overlay";
+
+/// Shows a small indicator on top of everything else whenever a GoXLR's mic is muted
+pub async fn run(handle: AppHandle) {
+    let config = handle
+        .state::<Mutex<Config>>()
+        .lock()
+        .unwrap()
+        .mic_overlay
+        .clone();
+    if !config.enabled {
+        return;
+    }
+    info!("Mic mute overlay enabled");
+
+    let mut changes = handle.state::<StateStore>().subscribe();
+    loop {
+        match changes.recv().await {
+            Ok(Change::VolumeChanged { .. }) | Ok(Change::ProfileChanged { .. }) => continue,
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+
+        // If we've lost the Utility, or the device has gone, the overlay shouldn't be displayed.
+        let status = handle.state::<StateStore>().get_status();
+        let muted = status.as_ref().is_some_and(is_any_muted);
+        if let Err(e) = set_visible(&handle, &config, muted) {
+            warn!("Unable to update the mic overlay: {}", e);
+        }
+    }
+}
+
+fn is_any_muted(status: &Value) -> bool {
+    get_devices(status)
+        .iter()
+        .any(|device| get_mixer(status, &device.serial).is_some_and(is_mic_muted))
+}
+
+fn set_visible(handle: &AppHandle, config: &OverlayConfig, visible: bool) -> tauri::Result<()> {
+    let window = handle.get_webview_window(OVERLAY_WINDOW_NAME);
+    match (window, visible) {
+        (Some(window), true) => window.show(),
+        (Some(window), false) => window.hide(),
+        (None, true) => create_window(handle, config)?.show(),
+        (None, false) => Ok(()),
+    }
+}
+
+fn create_window(handle: &AppHandle, config: &OverlayConfig) -> tauri::Result<WebviewWindow> {
+    // The page reads this when it loads, and sets its own opacity from it.
+    let script = format!(
+        "window.OVERLAY_OPACITY = {};",
+        config.opacity.clamp(0.1, 1.0)
+    );
+
+    let size = LogicalSize::new(config.width, config.height);
+    let window = create_floating_window(
+        handle,
+        OVERLAY_WINDOW_NAME,
+        "overlay.html",
+        "Mic Muted",
+        size,
+        &script,
+    )?;
+    window.set_position(LogicalPosition::new(config.x, config.y))?;
+    Ok(window)
+}
+
+/// Builds a hidden, undecorated window which sits on top of everything else, without taking
+/// focus or getting in the way of whatever's underneath it. Used by both the overlay and the OSD.
+pub fn create_floating_window(
+    handle: &AppHandle,
+    label: &str,
+    page: &str,
+    title: &str,
+    size: LogicalSize<f64>,
+    script: &str,
+) -> tauri::Result<WebviewWindow> {
+    let builder = WebviewWindowBuilder::new(handle, label, WebviewUrl::App(page.into()))
+        .title(title)
+        .inner_size(size.width, size.height)
+        .decorations(false)
+        .resizable(false)
+        .shadow(false)
+        .always_on_top(true)
+        .visible_on_all_workspaces(true)
+        .skip_taskbar(true)
+        .focused(false)
+        .visible(false)
+        .initialization_script(script);
+
+    // Transparency on macOS needs private APIs, so these will simply be opaque there.
+    #[cfg(not(target_os = "macos"))]
+    let builder = builder.transparent(true);
+
+    let window = builder.build()?;
+    window.set_ignore_cursor_events(true)?;
+    Ok(window)
+}
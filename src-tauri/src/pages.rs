@@ -0,0 +1,38 @@
+use crate::{show_window, WINDOW_NAME};
+use log::error;
+use tauri::{AppHandle, Manager, Url};
+
+/// Returns the URL of one of our bundled pages, these are served from a custom protocol which
+/// differs on Windows.
+pub fn get_local_url(page: &str) -> Url {
+    let base = if cfg!(windows) {
+        "http://tauri.localhost/"
+    } else {
+        "tauri://localhost/"
+    };
+    // These are constant, so will always parse..
+    Url::parse(base).and_then(|base| base.join(page)).unwrap()
+}
+
+/// Replaces the content of the main window with an error page, along with a hint on how the
+/// user may be able to fix the problem.
+pub fn show_error_page(handle: &AppHandle, message: &str, hint: &str) {
+    let mut url = get_local_url("error.html");
+    url.query_pairs_mut()
+        .append_pair("error", message)
+        .append_pair("hint", hint);
+
+    if let Some(window) = handle.get_webview_window(WINDOW_NAME) {
+        if let Err(e) = window.navigate(url) {
+            error!("Unable to display error page: {}", e);
+        }
+        show_window(&window);
+    }
+}
+
+/// Returns the main window to the loading page
+pub fn show_loading_page(handle: &AppHandle) {
+    if let Some(window) = handle.get_webview_window(WINDOW_NAME) {
+        let _ = window.navigate(get_local_url("index.html"));
+    }
+}
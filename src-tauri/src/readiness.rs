@@ -0,0 +1,188 @@
+use crate::{pages, startup, MonitorState, READY_EVENT_NAME};
+use log::{debug, error, info, warn};
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::{sleep, timeout};
+
+/// The paths which need to respond before we consider the Utility's UI to be usable
+static PROBE_PATHS: &[&str] = &["/", "/api/get-devices"];
+static PROBE_ATTEMPTS: u32 = 20;
+static PROBE_INTERVAL: Duration = Duration::from_millis(500);
+static PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+static PAGE_LOAD_TIMEOUT: Duration = Duration::from_secs(20);
+static PAGE_LOAD_ATTEMPTS: u32 = 3;
+static PAGE_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Tracks whether the Utility's UI has actually loaded in the main window
+#[derive(Default)]
+pub struct PageState {
+    loaded: AtomicBool,
+    ever_loaded: AtomicBool,
+    generation: AtomicU64,
+    failures: AtomicU32,
+}
+
+/// Waits for the Utility's HTTP server to be ready, then lets the window know it can load it. If
+/// it never becomes ready, the error page is displayed instead.
+pub async fn load_utility(handle: &AppHandle, http_address: &str) {
+    match wait_for_http(http_address).await {
+        Ok(()) => {
+            let _ = handle.emit(READY_EVENT_NAME, http_address);
+        }
+        Err(e) => {
+            error!("{}", e);
+            pages::show_error_page(
+                handle,
+                "The GoXLR Utility's interface isn't responding",
+                "The Utility may still be starting up, or its web interface may be disabled.",
+            );
+        }
+    }
+}
+
+/// Repeatedly probes the Utility's HTTP server until both the UI and the API respond
+pub async fn wait_for_http(http_address: &str) -> Result<(), String> {
+    let url = Url::parse(http_address).map_err(|e| format!("Invalid address: {}", e))?;
+    let host = match (url.host_str(), url.port_or_known_default()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        _ => return Err(format!("Unable to determine host from {}", url)),
+    };
+
+    let mut last_error = String::new();
+    for attempt in 1..=PROBE_ATTEMPTS {
+        match probe(&host).await {
+            Ok(()) => {
+                debug!("Utility HTTP server ready after {} attempt(s)", attempt);
+                return Ok(());
+            }
+            Err(e) => {
+                debug!("Utility HTTP server not ready (attempt {}): {}", attempt, e);
+                last_error = e;
+            }
+        }
+        sleep(PROBE_INTERVAL).await;
+    }
+    Err(format!(
+        "{} did not become ready: {}",
+        http_address, last_error
+    ))
+}
+
+async fn probe(host: &str) -> Result<(), String> {
+    for path in PROBE_PATHS {
+        let status = get_status_line(host, path)
+            .await
+            .map_err(|e| format!("{}: {}", path, e))?;
+
+        // We only care about the status code, eg. 'HTTP/1.1 200 OK'
+        let code = status.split_whitespace().nth(1).unwrap_or_default();
+        if !code.starts_with('2') {
+            return Err(format!("{} returned '{}'", path, status));
+        }
+    }
+    Ok(())
+}
+
+/// Performs a minimal HTTP request, and returns the status line of the response
+pub async fn get_status_line(host: &str, path: &str) -> Result<String, Error> {
+    let result = timeout(PROBE_TIMEOUT, async {
+        let mut stream = TcpStream::connect(host).await?;
+        let request = format!("GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, host);
+        stream.write_all(request.as_bytes()).await?;
+
+        let mut response = vec![0; 64];
+        let read = stream.read(&mut response).await?;
+        let response = String::from_utf8_lossy(&response[..read]).to_string();
+        Ok(response.lines().next().unwrap_or_default().to_string())
+    })
+    .await;
+
+    result.unwrap_or_else(|_| Err(Error::new(ErrorKind::TimedOut, "Timed out")))
+}
+
+/// Called when the main window starts loading the Utility, if the page hasn't reported in by the
+/// time the timeout expires, we assume the load failed and try again.
+pub fn watch_page_load(handle: &AppHandle) {
+    let state = handle.state::<PageState>();
+    state.loaded.store(false, Ordering::Relaxed);
+    let generation = state.generation.fetch_add(1, Ordering::Relaxed) + 1;
+
+    tokio::task::spawn(check_page_load(handle.clone(), generation));
+}
+
+/// Called by the injected script once the Utility's UI is running in the main window
+pub fn set_page_loaded(handle: &AppHandle) {
+    let state = handle.state::<PageState>();
+    if !state.loaded.swap(true, Ordering::Relaxed) {
+        debug!("Utility UI loaded");
+        startup::complete(handle);
+        state.failures.store(0, Ordering::Relaxed);
+        if !state.ever_loaded.swap(true, Ordering::Relaxed) {
+            // The renderer is clearly working, there's no need to work around a previous crash.
+            #[cfg(target_os = "linux")]
+            crate::renderer::clear_crash();
+        }
+    }
+}
+
+/// Whether the Utility's UI is currently loaded in the main window
+pub fn is_loaded(handle: &AppHandle) -> bool {
+    handle.state::<PageState>().loaded.load(Ordering::Relaxed)
+}
+
+/// Whether the Utility's UI has successfully loaded at any point since we started
+#[cfg(target_os = "linux")]
+pub fn has_loaded(handle: &AppHandle) -> bool {
+    handle
+        .state::<PageState>()
+        .ever_loaded
+        .load(Ordering::Relaxed)
+}
+
+async fn check_page_load(handle: AppHandle, generation: u64) {
+    sleep(PAGE_LOAD_TIMEOUT).await;
+
+    let state = handle.state::<PageState>();
+    let is_current = || state.generation.load(Ordering::Relaxed) == generation;
+    if !is_current() || state.loaded.load(Ordering::Relaxed) {
+        return;
+    }
+
+    // If we've lost the Utility, the monitor is already dealing with it.
+    let monitor = handle.state::<MonitorState>();
+    if !monitor.connected.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let failures = state.failures.fetch_add(1, Ordering::Relaxed) + 1;
+    warn!("The Utility's UI failed to load (attempt {})", failures);
+
+    let message = "The GoXLR Utility's interface failed to load";
+    if failures >= PAGE_LOAD_ATTEMPTS {
+        warn!(
+            "Giving up loading the Utility's UI after {} attempts",
+            failures
+        );
+        let hint = "Make sure the Utility's web interface is enabled, then try again.";
+        pages::show_error_page(&handle, message, hint);
+        return;
+    }
+    let hint = "The Utility may still be starting up, the app will try again shortly.";
+    pages::show_error_page(&handle, message, hint);
+
+    // Give the Utility a moment, then try again (unless something else has already done so)
+    sleep(PAGE_RETRY_DELAY).await;
+    if !is_current() {
+        return;
+    }
+    let address = monitor.address.lock().unwrap().clone();
+    if let Some(address) = address {
+        info!("Retrying load of the Utility's UI");
+        load_utility(&handle, &address).await;
+    }
+}
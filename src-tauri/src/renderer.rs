@@ -0,0 +1,227 @@
+use crate::config::{get_data_dir, Config, RendererFlag};
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::env;
+use std::fs::{create_dir_all, read_to_string, remove_file, write};
+use std::path::PathBuf;
+
+/// Allows the renderer flags to be overridden for a single run, eg. `disable_dmabuf,force_x11`
+static FLAGS_VARIABLE: &str = "GOXLR_UI_RENDERER_FLAGS";
+static CRASH_MARKER: &str = "renderer-crash";
+
+/// The flags used after the renderer has crashed, these avoid most known webkit2gtk problems at
+/// the cost of some performance.
+static COMPATIBILITY_FLAGS: &[RendererFlag] = &[
+    RendererFlag::DisableDmabuf,
+    RendererFlag::DisableCompositing,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Session {
+    Wayland,
+    X11,
+    Unknown,
+}
+
+/// Works out which webkit2gtk workarounds are needed, and sets the environment accordingly. This
+/// needs to be called before the webview is created.
+///
+/// In order of priority, flags come from the environment, then the config, then if the renderer
+/// crashed on a previous run the compatibility flags are used, otherwise they're picked based on
+/// the session type.
+pub fn configure(config: &Config) {
+    let session = get_session();
+    let flags = if let Some(flags) = get_environment_flags() {
+        info!("Using renderer flags from {}", FLAGS_VARIABLE);
+        flags
+    } else if let Some(flags) = &config.renderer_flags {
+        flags.clone()
+    } else if let Some(previous) = get_previous_crash() {
+        warn!(
+            "Renderer crashed on a previous run ({}), using compatibility flags",
+            previous
+        );
+        COMPATIBILITY_FLAGS.to_vec()
+    } else {
+        get_session_flags(session)
+    };
+    info!("Session: {:?}, Renderer Flags: {:?}", session, flags);
+
+    for flag in flags {
+        let (key, value) = get_variable(flag);
+
+        // If the user has set this themselves, they know better than we do.
+        if env::var_os(key).is_some() {
+            debug!("{} is already set, leaving it alone", key);
+            continue;
+        }
+        env::set_var(key, value);
+    }
+}
+
+/// Records that the renderer failed, so the next launch can try the compatibility flags
+pub fn record_crash() {
+    let Some(path) = get_crash_marker() else {
+        return;
+    };
+
+    // Note down what was in use, this is mostly useful for diagnostics.
+    let variables: Vec<String> = [
+        RendererFlag::DisableDmabuf,
+        RendererFlag::DisableCompositing,
+        RendererFlag::SoftwareRendering,
+        RendererFlag::ForceX11,
+    ]
+    .into_iter()
+    .filter_map(|flag| {
+        let (key, _) = get_variable(flag);
+        env::var(key).ok().map(|value| format!("{}={}", key, value))
+    })
+    .collect();
+
+    if let Some(parent) = path.parent() {
+        let _ = create_dir_all(parent);
+    }
+    if let Err(e) = write(&path, variables.join(" ")) {
+        warn!("Unable to record renderer crash: {}", e);
+    }
+}
+
+/// Called once the UI has loaded, so the next launch goes back to the usual flags rather than
+/// using the compatibility flags forever after a single crash
+pub fn clear_crash() {
+    let Some(path) = get_crash_marker() else {
+        return;
+    };
+    if path.exists() {
+        info!("UI loaded successfully, clearing the previous renderer crash");
+        if let Err(e) = remove_file(&path) {
+            warn!("Unable to clear renderer crash: {}", e);
+        }
+    }
+}
+
+/// Returns the location of the crash marker, if it exists
+pub fn get_crash_marker() -> Option<PathBuf> {
+    get_data_dir().map(|dir| dir.join(CRASH_MARKER))
+}
+
+fn get_previous_crash() -> Option<String> {
+    let path = get_crash_marker()?;
+    if !path.exists() {
+        return None;
+    }
+    let flags = read_to_string(path).unwrap_or_default();
+    Some(if flags.is_empty() {
+        "no flags".into()
+    } else {
+        flags
+    })
+}
+
+fn get_session() -> Session {
+    match env::var("XDG_SESSION_TYPE").as_deref() {
+        Ok("wayland") => Session::Wayland,
+        Ok("x11") => Session::X11,
+        _ => {
+            if env::var_os("WAYLAND_DISPLAY").is_some() {
+                Session::Wayland
+            } else if env::var_os("DISPLAY").is_some() {
+                Session::X11
+            } else {
+                Session::Unknown
+            }
+        }
+    }
+}
+
+fn get_session_flags(session: Session) -> Vec<RendererFlag> {
+    match session {
+        // The DMABUF renderer prevents webkit from launching correctly on a number of Wayland
+        // compositors (particularly with NVIDIA drivers).
+        Session::Wayland | Session::Unknown => vec![RendererFlag::DisableDmabuf],
+        Session::X11 => vec![],
+    }
+}
+
+fn get_environment_flags() -> Option<Vec<RendererFlag>> {
+    env::var(FLAGS_VARIABLE)
+        .ok()
+        .map(|value| parse_flags(&value))
+}
+
+/// Parses a comma separated list of flags, ignoring (and warning about) any we don't know
+fn parse_flags(value: &str) -> Vec<RendererFlag> {
+    let mut flags = vec![];
+    for name in value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        match serde_json::from_value(Value::String(name.into())) {
+            Ok(flag) => flags.push(flag),
+            Err(_) => warn!(
+                "Ignoring unknown renderer flag in {}: {}",
+                FLAGS_VARIABLE, name
+            ),
+        }
+    }
+    flags
+}
+
+fn get_variable(flag: RendererFlag) -> (&'static str, &'static str) {
+    match flag {
+        RendererFlag::DisableDmabuf => ("WEBKIT_DISABLE_DMABUF_RENDERER", "1"),
+        RendererFlag::DisableCompositing => ("WEBKIT_DISABLE_COMPOSITING_MODE", "1"),
+        RendererFlag::SoftwareRendering => ("LIBGL_ALWAYS_SOFTWARE", "1"),
+        RendererFlag::ForceX11 => ("GDK_BACKEND", "x11"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flags() {
+        assert_eq!(
+            parse_flags("disable_dmabuf,force_x11"),
+            vec![RendererFlag::DisableDmabuf, RendererFlag::ForceX11]
+        );
+        assert_eq!(
+            parse_flags(" software_rendering , disable_compositing "),
+            vec![
+                RendererFlag::SoftwareRendering,
+                RendererFlag::DisableCompositing
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_unknown_flags() {
+        assert_eq!(
+            parse_flags("disable_dmabuf,DisableDmabuf,go_faster"),
+            vec![RendererFlag::DisableDmabuf]
+        );
+    }
+
+    #[test]
+    fn empty_flags_disable_workarounds() {
+        // An empty variable is still an explicit choice, overriding the automatic flags
+        assert!(parse_flags("").is_empty());
+        assert!(parse_flags(" , ,").is_empty());
+    }
+
+    #[test]
+    fn session_flags() {
+        assert_eq!(
+            get_session_flags(Session::Wayland),
+            vec![RendererFlag::DisableDmabuf]
+        );
+        assert_eq!(
+            get_session_flags(Session::Unknown),
+            vec![RendererFlag::DisableDmabuf]
+        );
+        assert!(get_session_flags(Session::X11).is_empty());
+    }
+}
@@ -0,0 +1,74 @@
+use log::info;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Sent to the loading page whenever startup moves on to a new phase
+pub static PROGRESS_EVENT_NAME: &str = "STARTUP-PROGRESS";
+
+/// Tracks the progress of startup, so it can be displayed on the loading page, and so the time
+/// taken by each phase can be logged.
+pub struct StartupState {
+    started: Instant,
+    phase: Mutex<Option<(String, Instant)>>,
+    complete: Mutex<bool>,
+}
+
+impl StartupState {
+    pub fn new(started: Instant) -> Self {
+        Self {
+            started,
+            phase: Mutex::new(None),
+            complete: Mutex::new(false),
+        }
+    }
+}
+
+/// Moves startup on to a new phase, logging how long the previous one took
+pub fn phase(handle: &AppHandle, message: &str) {
+    let state = handle.state::<StartupState>();
+    let now = Instant::now();
+
+    let previous = state
+        .phase
+        .lock()
+        .unwrap()
+        .replace((message.to_string(), now));
+    if !*state.complete.lock().unwrap() {
+        if let Some((previous, started)) = previous {
+            info!("Startup: '{}' took {:?}", previous, now - started);
+        }
+    }
+    let _ = handle.emit(PROGRESS_EVENT_NAME, message);
+}
+
+/// Logs a startup milestone, without changing the displayed progress
+pub fn mark(handle: &AppHandle, milestone: &str) {
+    let state = handle.state::<StartupState>();
+    if !*state.complete.lock().unwrap() {
+        info!("Startup: {} after {:?}", milestone, state.started.elapsed());
+    }
+}
+
+/// Called once the Utility's UI has loaded, logs the total time taken
+pub fn complete(handle: &AppHandle) {
+    let state = handle.state::<StartupState>();
+    let mut complete = state.complete.lock().unwrap();
+    if *complete {
+        return;
+    }
+    *complete = true;
+
+    let now = Instant::now();
+    if let Some((previous, started)) = state.phase.lock().unwrap().take() {
+        info!("Startup: '{}' took {:?}", previous, now - started);
+    }
+    info!("Startup: completed in {:?}", now - state.started);
+}
+
+/// Returns the current phase, used by the loading page to catch up with anything it missed
+pub fn get_progress(handle: &AppHandle) -> Option<String> {
+    let state = handle.state::<StartupState>();
+    let phase = state.phase.lock().unwrap();
+    phase.as_ref().map(|(message, _)| message.clone())
+}
@@ -0,0 +1,378 @@
+use crate::devices::{get_devices, Device};
+use json_patch::Patch;
+use log::{debug, warn};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tokio::sync::broadcast::{self, Receiver, Sender};
+
+static CHANGE_CAPACITY: usize = 64;
+
+/// A change to the Utility's state, derived from the patches it sends
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// The state has been replaced entirely (eg. on connect), anything could have changed
+    Reset,
+
+    /// The connection to the Utility has been lost, and the state is no longer known
+    Disconnected,
+
+    DeviceAdded(Device),
+    DeviceRemoved(Device),
+    ProfileChanged {
+        serial: String,
+        profile: String,
+    },
+    MicMuteChanged {
+        serial: String,
+        muted: bool,
+    },
+    VolumeChanged {
+        serial: String,
+        channel: String,
+        volume: u8,
+    },
+}
+
+/// A Rust side copy of the Utility's `DaemonStatus`, seeded from `GetStatus` and kept up to date
+/// by the patches it sends over the websocket. Changes are broadcast to any subscribers.
+pub struct StateStore {
+    store: Mutex<Store>,
+    changes: Sender<Change>,
+}
+
+impl Default for StateStore {
+    fn default() -> Self {
+        Self {
+            store: Mutex::new(Store::default()),
+            changes: broadcast::channel(CHANGE_CAPACITY).0,
+        }
+    }
+}
+
+impl StateStore {
+    /// Handles a message from the Utility's websocket. An error indicates that our state can no
+    /// longer be trusted, and needs to be seeded again.
+    pub fn handle_message(&self, message: &str) -> Result<(), String> {
+        let message: Value =
+            serde_json::from_str(message).map_err(|e| format!("Invalid message: {}", e))?;
+        let Some(data) = message.get("data") else {
+            return Ok(());
+        };
+
+        let changes = if let Some(status) = data.get("Status") {
+            debug!("Received status from the Utility");
+            self.store.lock().unwrap().seed(status.clone())
+        } else if let Some(patch) = data.get("Patch") {
+            self.store.lock().unwrap().apply(patch)?
+        } else {
+            // Responses to anything else aren't relevant to us
+            return Ok(());
+        };
+        self.send(changes);
+        Ok(())
+    }
+
+    /// Forgets the state, called when the connection to the Utility is lost
+    pub fn clear(&self) {
+        self.store.lock().unwrap().clear();
+        self.send(vec![Change::Disconnected]);
+    }
+
+    /// Asks subscribers to refresh themselves from the current state
+    pub fn refresh(&self) {
+        self.send(vec![Change::Reset]);
+    }
+
+    pub fn subscribe(&self) -> Receiver<Change> {
+        self.changes.subscribe()
+    }
+
+    /// Returns a copy of the current `DaemonStatus`, if we have one
+    pub fn get_status(&self) -> Option<Value> {
+        self.store.lock().unwrap().status.clone()
+    }
+
+    fn send(&self, changes: Vec<Change>) {
+        for change in changes {
+            // This only fails when nothing is subscribed, which is fine.
+            let _ = self.changes.send(change);
+        }
+    }
+}
+
+/// The state itself, separate from the broadcasting so it can be tested on its own
+#[derive(Default)]
+struct Store {
+    status: Option<Value>,
+}
+
+impl Store {
+    fn seed(&mut self, status: Value) -> Vec<Change> {
+        self.status.replace(status);
+        vec![Change::Reset]
+    }
+
+    fn clear(&mut self) {
+        self.status = None;
+    }
+
+    fn apply(&mut self, patch: &Value) -> Result<Vec<Change>, String> {
+        let status = self
+            .status
+            .as_mut()
+            .ok_or("Received a patch before the status")?;
+        let patch: Patch =
+            serde_json::from_value(patch.clone()).map_err(|e| format!("Invalid patch: {}", e))?;
+
+        let before = get_snapshots(status);
+        if let Err(e) = json_patch::patch(status, &patch) {
+            // The patch is applied atomically, so the state is left as it was.
+            warn!("Unable to apply patch: {}", e);
+            return Err(e.to_string());
+        }
+        let after = get_snapshots(status);
+        Ok(get_changes(&before, &after))
+    }
+}
+
+/// The parts of each device's state which we report changes for
+#[derive(Debug)]
+struct Snapshot {
+    device: Device,
+    profile: Option<String>,
+    mic_muted: bool,
+    volumes: BTreeMap<String, u8>,
+}
+
+fn get_snapshots(status: &Value) -> BTreeMap<String, Snapshot> {
+    let mut snapshots = BTreeMap::new();
+    for device in get_devices(status) {
+        let Some(mixer) = get_mixer(status, &device.serial) else {
+            continue;
+        };
+
+        let profile = mixer.get("profile_name").and_then(Value::as_str);
+        let mut volumes = BTreeMap::new();
+        if let Some(levels) = mixer
+            .get("levels")
+            .and_then(|levels| levels.get("volumes"))
+            .and_then(Value::as_object)
+        {
+            for (channel, volume) in levels {
+                if let Some(volume) = volume.as_u64() {
+                    volumes.insert(channel.clone(), volume.min(u8::MAX as u64) as u8);
+                }
+            }
+        }
+
+        snapshots.insert(
+            device.serial.clone(),
+            Snapshot {
+                profile: profile.map(String::from),
+                mic_muted: is_mic_muted(mixer),
+                volumes,
+                device,
+            },
+        );
+    }
+    snapshots
+}
+
+fn get_changes(
+    before: &BTreeMap<String, Snapshot>,
+    after: &BTreeMap<String, Snapshot>,
+) -> Vec<Change> {
+    let mut changes = vec![];
+    for (serial, old) in before {
+        if !after.contains_key(serial) {
+            changes.push(Change::DeviceRemoved(old.device.clone()));
+        }
+    }
+
+    for (serial, new) in after {
+        let Some(old) = before.get(serial) else {
+            changes.push(Change::DeviceAdded(new.device.clone()));
+            continue;
+        };
+
+        if old.profile != new.profile {
+            if let Some(profile) = &new.profile {
+                changes.push(Change::ProfileChanged {
+                    serial: serial.clone(),
+                    profile: profile.clone(),
+                });
+            }
+        }
+        if old.mic_muted != new.mic_muted {
+            changes.push(Change::MicMuteChanged {
+                serial: serial.clone(),
+                muted: new.mic_muted,
+            });
+        }
+        for (channel, volume) in &new.volumes {
+            if old.volumes.get(channel) != Some(volume) {
+                changes.push(Change::VolumeChanged {
+                    serial: serial.clone(),
+                    channel: channel.clone(),
+                    volume: *volume,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Returns the status of a specific device
+pub fn get_mixer<'a>(status: &'a Value, serial: &str) -> Option<&'a Value> {
+    status.get("mixers").and_then(|mixers| mixers.get(serial))
+}
+
+pub fn is_mic_muted(mixer: &Value) -> bool {
+    // The mic can be muted either by the cough button, or by a fader assigned to the Mic.
+    let is_muted = |state: Option<&Value>| {
+        state
+            .and_then(Value::as_str)
+            .is_some_and(|state| state != "Unmuted")
+    };
+
+    if let Some(cough) = mixer.get("cough_button") {
+        if is_muted(cough.get("state")) {
+            return true;
+        }
+    }
+
+    if let Some(faders) = mixer.get("fader_status").and_then(Value::as_object) {
+        for fader in faders.values() {
+            let channel = fader.get("channel").and_then(Value::as_str);
+            if channel == Some("Mic") && is_muted(fader.get("mute_state")) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static STATUS: &str = include_str!("fixtures/status.json");
+    static PATCHES: &str = include_str!("fixtures/patches.jsonl");
+
+    /// The fixture spans several lines, so it's sent directly rather than through `replay`
+    fn seed_message() -> String {
+        format!(r#"{{"id": 0, "data": {{"Status": {}}}}}"#, STATUS)
+    }
+
+    fn collect(receiver: &mut Receiver<Change>) -> Vec<Change> {
+        let mut changes = vec![];
+        while let Ok(change) = receiver.try_recv() {
+            changes.push(change);
+        }
+        changes
+    }
+
+    /// Replays a recorded websocket session (one message per line) through a store, returning
+    /// every change produced
+    fn replay(store: &StateStore, messages: &str) -> Vec<Change> {
+        let mut receiver = store.subscribe();
+        for message in messages.lines().filter(|line| !line.trim().is_empty()) {
+            store.handle_message(message).unwrap();
+        }
+        collect(&mut receiver)
+    }
+
+    fn seeded() -> StateStore {
+        let store = StateStore::default();
+        store.handle_message(&seed_message()).unwrap();
+        store
+    }
+
+    #[test]
+    fn seeding_resets() {
+        let store = StateStore::default();
+        let mut receiver = store.subscribe();
+        store.handle_message(&seed_message()).unwrap();
+        assert_eq!(collect(&mut receiver), vec![Change::Reset]);
+        assert!(store.get_status().is_some());
+    }
+
+    #[test]
+    fn replays_recorded_patches() {
+        let store = seeded();
+        let changes = replay(&store, PATCHES);
+
+        let mini = Device {
+            serial: String::from("S220202153DI7"),
+            device_type: String::from("Mini"),
+        };
+        assert_eq!(
+            changes,
+            vec![
+                Change::VolumeChanged {
+                    serial: String::from("S201200586CQK"),
+                    channel: String::from("Mic"),
+                    volume: 180,
+                },
+                Change::MicMuteChanged {
+                    serial: String::from("S201200586CQK"),
+                    muted: true,
+                },
+                Change::MicMuteChanged {
+                    serial: String::from("S201200586CQK"),
+                    muted: false,
+                },
+                Change::ProfileChanged {
+                    serial: String::from("S201200586CQK"),
+                    profile: String::from("Streaming"),
+                },
+                Change::DeviceAdded(mini.clone()),
+                Change::DeviceRemoved(mini),
+            ]
+        );
+
+        let status = store.get_status().unwrap();
+        let mixer = get_mixer(&status, "S201200586CQK").unwrap();
+        assert_eq!(mixer["profile_name"], "Streaming");
+        assert_eq!(mixer["levels"]["volumes"]["Mic"], 180);
+        assert!(get_mixer(&status, "S220202153DI7").is_none());
+    }
+
+    #[test]
+    fn failed_patch_leaves_state_unchanged() {
+        let store = seeded();
+        let before = store.get_status();
+
+        // The first operation is valid, but the second isn't, so neither should be applied.
+        let message = r#"{"id": 18446744073709551615, "data": {"Patch": [
+            {"op": "replace", "path": "/mixers/S201200586CQK/profile_name", "value": "Broken"},
+            {"op": "remove", "path": "/mixers/S000000000000"}
+        ]}}"#;
+        assert!(store.handle_message(message).is_err());
+        assert_eq!(store.get_status(), before);
+    }
+
+    #[test]
+    fn patch_before_seed_is_an_error() {
+        let store = StateStore::default();
+        let message = r#"{"id": 18446744073709551615, "data": {"Patch": []}}"#;
+        assert!(store.handle_message(message).is_err());
+    }
+
+    #[test]
+    fn ignores_unrelated_responses() {
+        let store = seeded();
+        let message = r#"{"id": 1, "data": "Ok"}"#;
+        assert!(replay(&store, message).is_empty());
+    }
+
+    #[test]
+    fn clearing_disconnects() {
+        let store = seeded();
+        let mut receiver = store.subscribe();
+        store.clear();
+        assert_eq!(receiver.try_recv().unwrap(), Change::Disconnected);
+        assert!(store.get_status().is_none());
+    }
+}
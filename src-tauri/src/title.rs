@@ -0,0 +1,77 @@
+use crate::devices::{self, Device};
+use crate::state::{get_mixer, is_mic_muted};
+use crate::{MonitorState, WINDOW_NAME};
+use serde_json::Value;
+use std::sync::atomic::Ordering;
+use tauri::window::{ProgressBarState, ProgressBarStatus};
+use tauri::{AppHandle, Manager, UserAttentionType, WebviewWindow};
+
+static BASE_TITLE: &str = "GoXLR Utility";
+static SEPARATOR: &str = " — ";
+
+/// Updates the titles of all windows to reflect the current device and profile state
+pub fn update_titles(handle: &AppHandle, status: &Value, devices: &[Device]) {
+    // While we're reconnecting, the title is already set and shouldn't be replaced.
+    if !handle
+        .state::<MonitorState>()
+        .connected
+        .load(Ordering::Relaxed)
+    {
+        return;
+    }
+
+    if devices.is_empty() {
+        if let Some(window) = handle.get_webview_window(WINDOW_NAME) {
+            let _ = window.set_title(&format!("{}{}No GoXLR Connected", BASE_TITLE, SEPARATOR));
+        }
+        return;
+    }
+
+    for device in devices {
+        let label = devices::get_window_label(devices, &device.serial);
+        if let Some(window) = handle.get_webview_window(&label) {
+            let _ = window.set_title(&get_device_title(status, device));
+        }
+    }
+}
+
+/// Called when the connection to the Utility is lost, and we're attempting to get it back
+pub fn set_reconnecting(handle: &AppHandle) {
+    for window in devices::get_ui_windows(handle) {
+        let _ = window.set_title(&format!("{}{}Reconnecting..", BASE_TITLE, SEPARATOR));
+        set_progress(&window, ProgressBarStatus::Indeterminate);
+
+        if !window.is_focused().unwrap_or(false) {
+            let _ = window.request_user_attention(Some(UserAttentionType::Informational));
+        }
+    }
+}
+
+/// Called when the connection has been restored, the titles will be corrected on the next update
+pub fn set_connected(handle: &AppHandle) {
+    for window in devices::get_ui_windows(handle) {
+        let _ = window.set_title(BASE_TITLE);
+        set_progress(&window, ProgressBarStatus::None);
+        let _ = window.request_user_attention(None);
+    }
+}
+
+fn set_progress(window: &WebviewWindow, status: ProgressBarStatus) {
+    let _ = window.set_progress_bar(ProgressBarState {
+        status: Some(status),
+        progress: None,
+    });
+}
+
+pub fn get_device_title(status: &Value, device: &Device) -> String {
+    let mut parts = vec![String::from(device.model())];
+    if let Some(mixer) = get_mixer(status, &device.serial) {
+        if let Some(profile) = mixer.get("profile_name").and_then(Value::as_str) {
+            parts.push(format!("{} profile", profile));
+        }
+        if is_mic_muted(mixer) {
+            parts.push(String::from("Mic muted"));
+        }
+    }
+    parts.join(SEPARATOR)
+}
@@ -0,0 +1,118 @@
+use crate::devices::Device;
+use crate::folders::{self, Folder};
+use crate::{QUIT_EVENT_NAME, SHOW_DEVICE_EVENT_NAME, SHOW_EVENT_NAME};
+use log::warn;
+use tauri::menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Wry};
+
+static TRAY_ID: &str = "main";
+static MENU_SHOW: &str = "show";
+static MENU_QUIT: &str = "quit";
+static MENU_DEVICE_PREFIX: &str = "device:";
+static MENU_FOLDER_PREFIX: &str = "folder:";
+
+/// Creates the (optional) tray icon, this is mostly useful when multiple GoXLRs are attached, as
+/// it allows switching between their windows.
+pub fn create_tray(handle: &AppHandle) -> tauri::Result<()> {
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("GoXLR Utility")
+        .menu(&build_menu(handle, &[])?)
+        .show_menu_on_left_click(false)
+        .on_menu_event(|app, event| {
+            let id = event.id().as_ref();
+            if id == MENU_SHOW {
+                let _ = app.emit(SHOW_EVENT_NAME, None::<String>);
+            } else if id == MENU_QUIT {
+                let _ = app.emit(QUIT_EVENT_NAME, None::<String>);
+            } else if let Some(serial) = id.strip_prefix(MENU_DEVICE_PREFIX) {
+                let _ = app.emit(SHOW_DEVICE_EVENT_NAME, serial);
+            } else if let Some(folder) = id.strip_prefix(MENU_FOLDER_PREFIX) {
+                if let Some(folder) = Folder::from_name(folder) {
+                    tokio::task::spawn(async move {
+                        if let Err(e) = folders::open(folder).await {
+                            warn!("{}", e);
+                        }
+                    });
+                }
+            }
+        })
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                let _ = tray.app_handle().emit(SHOW_EVENT_NAME, None::<String>);
+            }
+        });
+
+    if let Some(icon) = handle.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(handle)?;
+    Ok(())
+}
+
+/// Rebuilds the tray menu to reflect the currently attached devices
+pub fn update_tray(handle: &AppHandle, devices: &[Device]) -> tauri::Result<()> {
+    if let Some(tray) = handle.tray_by_id(TRAY_ID) {
+        tray.set_menu(Some(build_menu(handle, devices)?))?;
+    }
+    Ok(())
+}
+
+fn build_menu(handle: &AppHandle, devices: &[Device]) -> tauri::Result<Menu<Wry>> {
+    let menu = Menu::new(handle)?;
+    menu.append(&MenuItem::with_id(
+        handle,
+        MENU_SHOW,
+        "Show",
+        true,
+        None::<&str>,
+    )?)?;
+
+    if devices.len() > 1 {
+        menu.append(&PredefinedMenuItem::separator(handle)?)?;
+        for device in devices {
+            let id = format!("{}{}", MENU_DEVICE_PREFIX, device.serial);
+            menu.append(&MenuItem::with_id(
+                handle,
+                id,
+                device.name(),
+                true,
+                None::<&str>,
+            )?)?;
+        }
+    }
+
+    // The Utility's data folders, their locations are fetched when one is picked
+    let mut folders = vec![];
+    for folder in Folder::ALL {
+        let id = format!("{}{}", MENU_FOLDER_PREFIX, folder.name());
+        folders.push(MenuItem::with_id(
+            handle,
+            id,
+            folder.title(),
+            true,
+            None::<&str>,
+        )?);
+    }
+    let items: Vec<&dyn IsMenuItem<Wry>> = folders
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    menu.append(&Submenu::with_items(handle, "Open Folder", true, &items)?)?;
+
+    menu.append(&PredefinedMenuItem::separator(handle)?)?;
+    menu.append(&MenuItem::with_id(
+        handle,
+        MENU_QUIT,
+        "Quit",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
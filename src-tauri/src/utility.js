@@ -0,0 +1,45 @@
+// This script is injected into every page loaded by the wrapper. It reports the current page of the
+// Utility's UI back so it can be restored on the next launch, sends a heartbeat so a frozen page can
+// be detected, and handles the zoom hotkeys so they work regardless of what the UI does with them.
+(function () {
+    const invoke = window.__TAURI_INTERNALS__.invoke;
+
+    let lastRoute = null;
+    const reportRoute = () => {
+        const route = window.location.pathname + window.location.search + window.location.hash;
+        if (route !== lastRoute) {
+            lastRoute = route;
+            invoke("report_route", {route}).catch(() => {});
+        }
+    };
+
+    // The UI is a single page app, so we need to catch history changes as well as page loads.
+    window.addEventListener("hashchange", reportRoute);
+    window.addEventListener("popstate", reportRoute);
+    window.addEventListener("DOMContentLoaded", reportRoute);
+    setInterval(reportRoute, 1000);
+
+    // This needs to match the interval in watchdog.rs
+    setInterval(() => invoke("heartbeat").catch(() => {}), 2000);
+
+    document.addEventListener("keydown", (event) => {
+        if (!(event.ctrlKey || event.metaKey) || event.altKey) {
+            return;
+        }
+
+        let action = null;
+        if (event.key === "=" || event.key === "+") {
+            action = "In";
+        } else if (event.key === "-") {
+            action = "Out";
+        } else if (event.key === "0") {
+            action = "Reset";
+        }
+
+        if (action !== null) {
+            event.preventDefault();
+            event.stopPropagation();
+            invoke("zoom", {action}).catch(() => {});
+        }
+    }, true);
+})();
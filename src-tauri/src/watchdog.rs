@@ -0,0 +1,112 @@
+use crate::config::Config;
+use crate::readiness::is_loaded;
+use crate::{MonitorState, READY_EVENT_NAME, WINDOW_NAME};
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+/// This needs to match the interval in utility.js
+static HEARTBEAT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Briefly displayed in the UI once it's been reloaded
+static RECOVERED_NOTICE: &str = r#"
+(function () {
+    const notice = document.createElement("div");
+    notice.textContent = "UI recovered";
+    notice.style.cssText = "position: fixed; bottom: 20px; left: 50%; transform: translateX(-50%);" +
+        "padding: 8px 16px; border-radius: 4px; background: #353937; color: #fff; z-index: 99999;" +
+        "font-family: sans-serif; font-size: 14px; transition: opacity 0.5s; pointer-events: none;";
+    document.body.appendChild(notice);
+    setTimeout(() => notice.style.opacity = "0", 3000);
+    setTimeout(() => notice.remove(), 3500);
+})();
+"#;
+
+/// Tracks the heartbeats sent by the injected script in the main window
+pub struct WatchdogState {
+    last_beat: Mutex<Instant>,
+    recovering: AtomicBool,
+}
+
+impl Default for WatchdogState {
+    fn default() -> Self {
+        Self {
+            last_beat: Mutex::new(Instant::now()),
+            recovering: AtomicBool::new(false),
+        }
+    }
+}
+
+/// Called by the injected script every `HEARTBEAT_INTERVAL` while the Utility's UI is loaded
+pub fn heartbeat(window: &WebviewWindow) {
+    let state = window.state::<WatchdogState>();
+    *state.last_beat.lock().unwrap() = Instant::now();
+
+    // If this is the first beat after a reload, let the user know things are working again.
+    if state.recovering.swap(false, Ordering::Relaxed) {
+        info!("Utility UI recovered");
+        let _ = window.eval(RECOVERED_NOTICE);
+    }
+}
+
+/// Periodically checks that the main window is still sending heartbeats, and reloads it if not
+pub async fn run(handle: AppHandle) {
+    let missed_beats = handle
+        .state::<Mutex<Config>>()
+        .lock()
+        .unwrap()
+        .heartbeat_missed_beats;
+    if missed_beats == 0 {
+        info!("UI watchdog disabled");
+        return;
+    }
+    let limit = HEARTBEAT_INTERVAL * missed_beats;
+
+    loop {
+        tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+
+        let state = handle.state::<WatchdogState>();
+        let Some(window) = handle.get_webview_window(WINDOW_NAME) else {
+            continue;
+        };
+
+        // Hidden pages may have their timers throttled, and we only expect heartbeats while the
+        // Utility is connected and its UI is loaded, so reset the clock until that's the case.
+        let connected = handle
+            .state::<MonitorState>()
+            .connected
+            .load(Ordering::Relaxed);
+        let visible = window.is_visible().unwrap_or(false);
+        if !connected || !visible || !is_loaded(&handle) {
+            *state.last_beat.lock().unwrap() = Instant::now();
+            continue;
+        }
+
+        let elapsed = state.last_beat.lock().unwrap().elapsed();
+        if elapsed >= limit {
+            let reason = format!("No heartbeat from the UI for {}s", elapsed.as_secs());
+            recover(&handle, &reason);
+        }
+    }
+}
+
+/// Reloads the Utility's UI in the main window
+pub fn recover(handle: &AppHandle, reason: &str) {
+    warn!("{}, reloading the UI", reason);
+
+    let state = handle.state::<WatchdogState>();
+    *state.last_beat.lock().unwrap() = Instant::now();
+    state.recovering.store(true, Ordering::Relaxed);
+
+    let address = handle
+        .state::<MonitorState>()
+        .address
+        .lock()
+        .unwrap()
+        .clone();
+    if let Some(address) = address {
+        let _ = handle.emit(READY_EVENT_NAME, address);
+    }
+}